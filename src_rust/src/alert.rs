@@ -0,0 +1,141 @@
+// 告警处理：越限时执行 --on-crit 钩子，维护窗口内抑制告警与非零退出码
+use crate::cli::QueryArgs;
+use crate::{exit_code, state, status_severity, DiskInfo};
+use std::process::Command;
+
+// 当前处于维护窗口时返回原因
+fn suppression_reason(query: &QueryArgs) -> Option<String> {
+    if let Some(until) = query.quiet_until {
+        if state::now_secs() < until {
+            return Some(format!("--quiet-until {until}"));
+        }
+    }
+    query
+        .maintenance_file
+        .as_ref()
+        .filter(|path| path.exists())
+        .map(|path| format!("maintenance file {}", path.display()))
+}
+
+// 有设备达到 CRIT 时执行 --on-crit 命令，设备列表通过环境变量传入；
+// 设置了 --alert-cooldown 时跳过冷却中的设备，并记录本次告警的设备
+fn run_on_crit(command: &str, results: &[DiskInfo], query: &QueryArgs) {
+    let now = state::now_secs();
+    let log_path = state::AlertLog::path_for(&query.state_file);
+    let mut log = query
+        .alert_cooldown
+        .map(|_| state::AlertLog::load(&log_path));
+    let critical: Vec<&str> = results
+        .iter()
+        .filter(|info| info.status == "CRIT")
+        .map(|info| info.device.as_str())
+        .filter(|device| {
+            !log.as_ref()
+                .zip(query.alert_cooldown)
+                .is_some_and(|(log, cooldown)| log.cooling_down(device, cooldown, now))
+        })
+        .collect();
+    if critical.is_empty() {
+        return;
+    }
+    match Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("HDDTEMP_CRIT_DEVICES", critical.join(" "))
+        .status()
+    {
+        Ok(status) if !status.success() => eprintln!("--on-crit command exited with {status}"),
+        Ok(_) => {}
+        Err(e) => eprintln!("Failed to run --on-crit command: {e}"),
+    }
+    if let Some(log) = &mut log {
+        for device in critical {
+            log.last_alert.insert(device.to_string(), now);
+        }
+        if let Err(e) = log.save(&log_path) {
+            eprintln!("Failed to save alert log {}: {e}", log_path.display());
+        }
+    }
+}
+
+// 设备导致非零退出码的原因，如 "/dev/sdc=61°C exceeds crit=55°C"
+fn reason(info: &DiskInfo) -> String {
+    let device = &info.device;
+    let temp = info
+        .temperature
+        .map(|t| format!("{t}°C"))
+        .unwrap_or_default();
+    let limit = |value: Option<f64>| value.map(|v| format!("{v}°C")).unwrap_or_default();
+    let thresholds = info.thresholds.unwrap_or_default();
+    match info.status.as_str() {
+        "CRIT" if thresholds.crit_from_rated => format!(
+            "{device}={temp} exceeds crit={} (rated max {} minus --rated-margin)",
+            limit(thresholds.crit_c),
+            limit(info.rated_max_c)
+        ),
+        "CRIT" => format!("{device}={temp} exceeds crit={}", limit(thresholds.crit_c)),
+        "WARN" => format!("{device}={temp} exceeds warn={}", limit(thresholds.warn_c)),
+        "FUZZY" => format!("{device}={temp} only came from the text fallback (--strict-json)"),
+        "SUSPECT" => format!(
+            "{device}={temp} looks suspect: {}",
+            info.suspect.as_deref().unwrap_or("failed --sanity")
+        ),
+        "NOTEMP" => format!("{device} reports no temperature (--warn-on-missing-temp)"),
+        "PREFAIL" => format!(
+            "{device} has failing SMART attributes: {}",
+            info.failing_attributes.join(", ")
+        ),
+        "NVME-WARN" => format!(
+            "{device} reports NVMe critical warnings: {}",
+            info.nvme_critical_warning.join(", ")
+        ),
+        "FAIL" => format!(
+            "{device} could not be queried: {}",
+            info.error.as_deref().unwrap_or("unknown error")
+        ),
+        status => format!("{device} is {status}"),
+    }
+}
+
+// --explain-exit：在 stderr 说明退出码的来由，列出达到该严重程度的每个设备
+fn explain(results: &[DiskInfo], query: &QueryArgs, code: i32, suppressed: Option<&str>) {
+    if let Some(reason) = suppressed {
+        eprintln!("exit=0 because alerts are suppressed ({reason})");
+        return;
+    }
+    if code == 0 {
+        eprintln!("exit=0 because no drive is over a threshold or unhealthy");
+        return;
+    }
+    let reasons: Vec<String> = results
+        .iter()
+        .filter(|info| status_severity(&info.status, query) == code)
+        .map(reason)
+        .collect();
+    eprintln!("exit={code} because {}", reasons.join("; "));
+}
+
+// 处理一次扫描结果的告警，返回应使用的退出码；维护窗口内读数照常输出，只是不告警
+pub fn handle(results: &[DiskInfo], query: &QueryArgs) -> i32 {
+    let code = exit_code(results, query);
+    if code == 0 {
+        if query.explain_exit {
+            explain(results, query, 0, None);
+        }
+        return 0;
+    }
+    if let Some(reason) = suppression_reason(query) {
+        eprintln!("Alerts suppressed ({reason})");
+        if query.explain_exit {
+            explain(results, query, code, Some(&reason));
+        }
+        return 0;
+    }
+    if query.explain_exit {
+        explain(results, query, code, None);
+    }
+    if let Some(command) = &query.on_crit {
+        run_on_crit(command, results, query);
+    }
+    code
+}
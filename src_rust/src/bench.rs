@@ -0,0 +1,81 @@
+// --bench：重复完整扫描，统计总耗时、每个设备的耗时和实际达到的并行度，用于调整 --jobs
+use crate::Scanner;
+use std::collections::BTreeMap;
+use std::io;
+use std::time::{Duration, Instant};
+
+// 一组耗时的最小值、平均值和最大值
+struct Stats {
+    min: Duration,
+    mean: Duration,
+    max: Duration,
+}
+
+impl Stats {
+    fn of(samples: &[Duration]) -> Stats {
+        Stats {
+            min: samples.iter().copied().min().unwrap_or_default(),
+            mean: samples.iter().sum::<Duration>() / samples.len().max(1) as u32,
+            max: samples.iter().copied().max().unwrap_or_default(),
+        }
+    }
+}
+
+fn ms(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+pub fn run(scanner: &Scanner, runs: u32) -> io::Result<()> {
+    let mut totals = Vec::new();
+    let mut per_device: BTreeMap<String, Vec<Duration>> = BTreeMap::new();
+    // 所有设备耗时之和与总耗时之比即实际并行度
+    let mut busy = Duration::ZERO;
+
+    for run in 1..=runs {
+        let started = Instant::now();
+        let results = scanner.scan()?;
+        let total = started.elapsed();
+        println!(
+            "Run {run}/{runs}: {:.1} ms for {} devices",
+            ms(total),
+            results.len()
+        );
+        totals.push(total);
+        for info in results {
+            busy += info.elapsed;
+            per_device
+                .entry(info.device)
+                .or_default()
+                .push(info.elapsed);
+        }
+    }
+
+    let total = Stats::of(&totals);
+    println!();
+    println!(
+        "Total (ms): min {:.1}  mean {:.1}  max {:.1}",
+        ms(total.min),
+        ms(total.mean),
+        ms(total.max)
+    );
+    println!("Per device (ms):");
+    let width = per_device.keys().map(String::len).max().unwrap_or(0);
+    for (device, samples) in &per_device {
+        let stats = Stats::of(samples);
+        println!(
+            "  {device:<width$}  min {:.1}  mean {:.1}  max {:.1}",
+            ms(stats.min),
+            ms(stats.mean),
+            ms(stats.max)
+        );
+    }
+    let wall: Duration = totals.iter().sum();
+    if !wall.is_zero() {
+        println!(
+            "Effective parallelism: {:.2} ({} threads)",
+            busy.as_secs_f64() / wall.as_secs_f64(),
+            scanner.pool.current_num_threads()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,735 @@
+// 命令行参数定义（clap derive）
+use crate::{lock, remote, state};
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+// 帮助信息中的选项分组
+const OUTPUT: &str = "Output options";
+const QUERY: &str = "Query options";
+const CONFIG: &str = "Configuration";
+const DAEMON: &str = "Daemon options";
+const FILTER: &str = "Device selection";
+const THRESHOLD: &str = "Thresholds";
+
+// 多次采样时的聚合方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum RepeatAgg {
+    #[default]
+    Mean,
+    Max,
+    Min,
+}
+
+// 表格样式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum TableStyle {
+    // 无边框，列间留两个空格
+    #[default]
+    Default,
+    // 无边框，列间只留一个空格
+    Compact,
+    // Markdown 表格，可直接粘贴到文档或工单
+    Markdown,
+    // 只有列分隔线和表头下的横线
+    Borderless,
+}
+
+// 温度读数的来源（--backend）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Backend {
+    Smartctl,
+    // 内核 drivetemp 模块的 hwmon 传感器
+    Drivetemp,
+    // NVMe 设备用 nvme-cli 的 smart-log，其余用 smartctl
+    NvmeCli,
+    // 不带 --json 运行 smartctl，解析文本输出（smartmontools 7.0 之前的版本）
+    Text,
+    // 有 drivetemp 传感器的盘用 drivetemp，其余用 smartctl；smartctl 读不到温度的 NVMe 设备再试 nvme-cli
+    Auto,
+}
+
+// --assume-unit 可指定的原始温度单位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TempUnit {
+    Celsius,
+    Decicelsius,
+    Fahrenheit,
+    Kelvin,
+    Decikelvin,
+}
+
+// 解析 --assume-unit 的 DEVICE=UNIT，设备名可省略 /dev/
+fn parse_assumed_unit(value: &str) -> Result<(String, TempUnit), String> {
+    let (device, unit) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected DEVICE=UNIT, got {value:?}"))?;
+    let unit = TempUnit::from_str(unit, true)?;
+    Ok((format!("/dev/{}", device.trim_start_matches("/dev/")), unit))
+}
+
+// --label 的 DEVICE=NAME，设备名可省略 /dev/
+fn parse_label(value: &str) -> Result<(String, String), String> {
+    let (device, label) = value
+        .split_once('=')
+        .filter(|(_, label)| !label.is_empty())
+        .ok_or_else(|| format!("expected DEVICE=NAME, got {value:?}"))?;
+    Ok((
+        format!("/dev/{}", device.trim_start_matches("/dev/")),
+        label.to_string(),
+    ))
+}
+
+// --power-mode：传给 smartctl -n，决定处于低功耗状态的设备是否跳过查询
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PowerMode {
+    // 总是查询（smartctl 的默认行为）
+    Never,
+    Sleep,
+    Standby,
+    Idle,
+}
+
+impl PowerMode {
+    pub fn as_arg(self) -> &'static str {
+        match self {
+            PowerMode::Never => "never",
+            PowerMode::Sleep => "sleep",
+            PowerMode::Standby => "standby",
+            PowerMode::Idle => "idle",
+        }
+    }
+}
+
+// 人类可读输出的布局
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Layout {
+    // 表格放不下终端宽度时改为逐条记录
+    #[default]
+    Auto,
+    Table,
+    // 每个设备一段 "列名: 值"
+    Records,
+}
+
+// --format-preset 可选的输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum FormatPreset {
+    // 原版 hddtemp 命令的输出，每行 "/dev/sda: MODEL: 38°C"
+    Hddtemp,
+}
+
+// export 子命令支持的输出格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Csv,
+    // 严格的 OpenMetrics 文本格式（带 # UNIT 与 # EOF）
+    Openmetrics,
+    // 需要 yaml feature
+    #[cfg(feature = "yaml")]
+    Yaml,
+}
+
+/// Read hard disk temperatures through smartctl.
+///
+/// Without a subcommand, behaves like `read`. Every option can also be set through the
+/// HDDTEMP_* environment variable shown in its help; flags on the command line take precedence.
+#[derive(Debug, Parser)]
+#[command(version, about, args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+
+    #[command(flatten)]
+    pub read: ReadArgs,
+
+    /// Check GitHub for a newer release and exit (nothing is downloaded)
+    #[cfg(feature = "update-check")]
+    #[arg(long)]
+    pub check_update: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Commands {
+    /// List the devices that would be queried, one per line
+    List(ListArgs),
+    /// Query all drives and print a table (default)
+    Read(ReadArgs),
+    /// Serve readings over TCP using the classic hddtemp daemon protocol
+    Daemon(DaemonArgs),
+    /// Query all drives once and print machine-readable output
+    Export(ExportArgs),
+}
+
+// 设备发现相关的选项
+#[derive(Debug, Clone, Args)]
+pub struct DiscoveryArgs {
+    /// Query only these drives instead of every disk lsblk reports; accepts /dev paths,
+    /// LABEL=, UUID=, PARTLABEL= or PARTUUID=, and partitions resolve to their whole disk
+    #[arg(value_name = "DEVICE")]
+    pub devices: Vec<String>,
+
+    /// Discover drives by running COMMAND through sh instead of lsblk; each non-empty line
+    /// of its stdout is one device
+    #[arg(long, value_name = "COMMAND", conflicts_with = "devices", env = "HDDTEMP_LIST_CMD",
+          help_heading = FILTER)]
+    pub list_cmd: Option<String>,
+
+    /// lsblk binary used to discover drives; if it is not installed, drives are listed from
+    /// /sys/block instead
+    #[arg(long, value_name = "PATH", default_value = "lsblk", env = "HDDTEMP_LSBLK_PATH",
+          help_heading = FILTER)]
+    pub lsblk_path: PathBuf,
+
+    /// Also include md RAID arrays, device-mapper (LVM, crypt, multipath) devices and disks
+    /// without SMART (zram, virtio, VM-emulated disks, ...); by default only physical disks are
+    /// queried
+    #[arg(long, env = "HDDTEMP_INCLUDE_VIRTUAL", help_heading = FILTER)]
+    pub include_virtual: bool,
+
+    /// Only query discovered devices whose path matches the shell-style PATTERN, e.g. '/dev/nvme*'
+    /// (repeatable; a device matching any pattern is kept)
+    #[arg(long, value_name = "PATTERN", env = "HDDTEMP_DEVICES_GLOB", help_heading = FILTER)]
+    pub devices_glob: Vec<String>,
+
+    /// Skip devices whose path matches the regular expression REGEX anywhere, e.g.
+    /// 'nvme1n[0-9]+' or '^/dev/sd[c-e]$' (repeatable)
+    #[arg(long, value_name = "REGEX", env = "HDDTEMP_EXCLUDE_PATTERN", help_heading = FILTER)]
+    pub exclude_pattern: Vec<String>,
+
+    /// Skip DEVICE (repeatable, or comma-separated)
+    #[arg(long, value_name = "DEVICE", value_delimiter = ',', env = "HDDTEMP_EXCLUDE",
+          help_heading = FILTER)]
+    pub exclude: Vec<String>,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct ListArgs {
+    #[command(flatten)]
+    pub discovery: DiscoveryArgs,
+
+    #[command(flatten)]
+    pub smartctl: SmartctlArgs,
+
+    /// Print an inventory (vendor, model, serial, WWN, type) as JSON, using a lightweight
+    /// identify query (smartctl -i) that does not read temperatures
+    #[arg(long, env = "HDDTEMP_LIST_JSON", help_heading = OUTPUT)]
+    pub json: bool,
+
+    /// Indent JSON output instead of writing it compactly
+    #[arg(long, requires = "json", env = "HDDTEMP_PRETTY", help_heading = OUTPUT)]
+    pub pretty: bool,
+}
+
+// 如何调用 smartctl
+#[derive(Debug, Clone, Args)]
+pub struct SmartctlArgs {
+    /// smartctl binary to run
+    #[arg(long, value_name = "PATH", default_value = "smartctl", env = "HDDTEMP_SMARTCTL_PATH",
+          help_heading = QUERY)]
+    pub smartctl_path: PathBuf,
+
+    /// Run this command instead of smartctl, passing it the same arguments; may include fixed
+    /// leading arguments, e.g. "sudo -n /usr/sbin/smartctl" for least-privilege setups. Parsed
+    /// by sh, so paths and arguments containing spaces can be quoted
+    #[arg(long, value_name = "COMMAND", conflicts_with = "smartctl_path",
+          env = "HDDTEMP_EXEC_WRAPPER", help_heading = QUERY)]
+    pub exec_wrapper: Option<String>,
+
+    /// Only try these smartctl -d types, in this order, instead of the built-in list
+    /// ("default" means no -d), e.g. --types default,sat,nvme
+    #[arg(long, value_name = "TYPE", value_delimiter = ',', env = "HDDTEMP_TYPES",
+          help_heading = QUERY)]
+    pub types: Vec<String>,
+
+    /// Pass -n MODE to every smartctl call so it skips drives in that power state or lower
+    /// instead of waking them (status STANDBY): never, sleep, standby or idle
+    #[arg(long, value_enum, value_name = "MODE", env = "HDDTEMP_POWER_MODE", help_heading = QUERY)]
+    pub power_mode: Option<PowerMode>,
+
+    /// Seconds to wait for each smartctl call before killing it and retrying once with
+    /// --retry-timeout
+    #[arg(long, value_name = "SECONDS", default_value_t = 10, env = "HDDTEMP_TIMEOUT",
+          value_parser = clap::value_parser!(u64).range(1..), help_heading = QUERY)]
+    pub timeout: u64,
+
+    /// Seconds allowed for the single retry of a smartctl call that hit --timeout, for drives
+    /// that are occasionally slow to respond (e.g. large SMR disks); the drive fails if it
+    /// times out again
+    #[arg(long, value_name = "SECONDS", default_value_t = 60, env = "HDDTEMP_RETRY_TIMEOUT",
+          value_parser = clap::value_parser!(u64).range(1..), help_heading = QUERY)]
+    pub retry_timeout: u64,
+}
+
+// 各个查询类子命令共用的选项
+#[derive(Debug, Clone, Args)]
+pub struct QueryArgs {
+    #[command(flatten)]
+    pub discovery: DiscoveryArgs,
+
+    /// Only report drives matching EXPR: "field op value" joined by and/or, where field is
+    /// temp, device, vendor, model or status, e.g. 'temp > 50 and vendor == Seagate'
+    #[arg(long, value_name = "EXPR", env = "HDDTEMP_FILTER", help_heading = FILTER)]
+    pub filter: Option<String>,
+
+    /// Also query the drives of HOST (user@host) over SSH by running --remote-command there,
+    /// adding a HOST column; may be repeated, and an unreachable host shows up as one FAIL row
+    #[arg(long, value_name = "USER@HOST", value_delimiter = ',', env = "HDDTEMP_REMOTE",
+          help_heading = FILTER)]
+    pub remote: Vec<String>,
+
+    /// Command run on each --remote host; it must print a JSON report like export --format json
+    /// (e.g. "sudo -n hddtemp_rust export --format json")
+    #[arg(long, value_name = "COMMAND", default_value = remote::DEFAULT_REMOTE_COMMAND,
+          env = "HDDTEMP_REMOTE_COMMAND", help_heading = FILTER)]
+    pub remote_command: String,
+
+    /// With --remote, only report the remote hosts' drives; local root is then not required
+    #[arg(long, requires = "remote", env = "HDDTEMP_REMOTE_ONLY", help_heading = FILTER)]
+    pub remote_only: bool,
+
+    #[command(flatten)]
+    pub smartctl: SmartctlArgs,
+
+    /// Mark drives that were in standby and got woken by the query with status WAKEUP
+    #[arg(long, env = "HDDTEMP_ERROR_ON_WAKEUP", help_heading = QUERY)]
+    pub error_on_wakeup: bool,
+
+    /// Flag temperatures recovered by the fuzzy text fallback instead of smartctl JSON
+    /// with status FUZZY and exit code 1
+    #[arg(long, env = "HDDTEMP_STRICT_JSON", help_heading = QUERY)]
+    pub strict_json: bool,
+
+    /// For SAS/SCSI drives, also fetch the environmental reporting log (smartctl -l envrep)
+    /// for extra temperature data such as lifetime maximum
+    #[arg(long, env = "HDDTEMP_SCSI_ENVREP", help_heading = QUERY)]
+    pub scsi_envrep: bool,
+
+    /// Where to read temperatures from: smartctl; drivetemp, the kernel's hwmon driver for
+    /// SATA/SAS disks (no smartctl and no spin-up, but less identity data); nvme-cli, which reads
+    /// NVMe drives with `nvme smart-log` and the rest with smartctl; text, which parses plain
+    /// `smartctl -a` output for smartmontools older than 7.0; or auto, which uses drivetemp where
+    /// it has a sensor for the drive, smartctl otherwise, and nvme-cli for NVMe drives smartctl
+    /// gets no temperature from
+    #[arg(long, value_enum, default_value_t = Backend::Smartctl, env = "HDDTEMP_BACKEND",
+          help_heading = QUERY)]
+    pub backend: Backend,
+
+    /// Also report persistent memory DIMMs (e.g. Optane PMem) with their media temperature from
+    /// `ndctl list --dimms --health`; their /dev/pmem* namespaces are then not queried with smartctl
+    #[arg(long, env = "HDDTEMP_PMEM", help_heading = QUERY)]
+    pub pmem: bool,
+
+    /// Look up ZFS pool (`zpool status`) and btrfs filesystem (`btrfs filesystem show`)
+    /// membership, report each drive's pool and vdev, and summarize temperatures per vdev with
+    /// its hottest drive
+    #[arg(long, env = "HDDTEMP_POOLS", help_heading = QUERY)]
+    pub pools: bool,
+
+    /// Flag drives whose readings look fake (status SUSPECT): outside plausible bounds, or stuck
+    /// at a known bogus value such as 0 or 128 across all --repeat samples
+    #[arg(long, env = "HDDTEMP_SANITY", help_heading = QUERY)]
+    pub sanity: bool,
+
+    /// Treat drives that return no temperature as a warning (status NOTEMP, exit code 1)
+    /// instead of OK; failed queries also count towards the exit code
+    #[arg(long, env = "HDDTEMP_WARN_ON_MISSING_TEMP", help_heading = QUERY)]
+    pub warn_on_missing_temp: bool,
+
+    /// Report this ATA attribute as the temperature: 194 (Temperature_Celsius) or 190
+    /// (Airflow_Temperature_Cel); drives without it keep the default reading
+    #[arg(long, value_name = "ID", value_parser = PossibleValuesParser::new(["194", "190"]).map(|id| id.parse::<u32>().unwrap_or_default()), env = "HDDTEMP_TEMP_ATTR",
+          help_heading = QUERY)]
+    pub temp_attr: Option<u32>,
+
+    /// Show every ATA temperature attribute (194 and 190) next to the reported temperature
+    #[arg(long, env = "HDDTEMP_ALL_TEMPS", help_heading = QUERY)]
+    pub all_temps: bool,
+
+    /// Show each NVMe temperature sensor (e.g. controller and NAND) next to the composite
+    /// temperature, which stays the reported reading; --flat adds nvme0n1:sensor1=... pairs
+    #[arg(long, env = "HDDTEMP_ALL_SENSORS", help_heading = QUERY)]
+    pub all_sensors: bool,
+
+    /// Truncate vendor, model and serial strings from smartctl longer than N characters (with
+    /// an ellipsis and a note on stderr), guarding against pathological drive firmware
+    #[arg(long, value_name = "N", default_value_t = 256, env = "HDDTEMP_MAX_FIELD_LEN",
+          value_parser = clap::value_parser!(u32).range(2..), help_heading = QUERY)]
+    pub max_field_len: u32,
+
+    /// Sample each drive N times and aggregate the readings
+    #[arg(long, value_name = "N", default_value_t = 1, env = "HDDTEMP_REPEAT",
+          value_parser = clap::value_parser!(u32).range(1..), help_heading = QUERY)]
+    pub repeat: u32,
+
+    /// How to aggregate samples taken with --repeat
+    #[arg(long, value_enum, default_value_t = RepeatAgg::Mean, env = "HDDTEMP_REPEAT_AGG",
+          help_heading = QUERY)]
+    pub repeat_agg: RepeatAgg,
+
+    /// Interpret DEVICE's raw temperature readings as UNIT instead of auto-detecting a wrong
+    /// unit (celsius, decicelsius, fahrenheit, kelvin, decikelvin), e.g. /dev/sdb=decikelvin;
+    /// may be repeated
+    #[arg(long, value_name = "DEVICE=UNIT", value_parser = parse_assumed_unit,
+          env = "HDDTEMP_ASSUME_UNIT", value_delimiter = ',', help_heading = QUERY)]
+    pub assume_unit: Vec<(String, TempUnit)>,
+
+    /// Number of drives to query in parallel (default: number of CPUs)
+    #[arg(short, long, value_name = "N", env = "HDDTEMP_JOBS",
+          value_parser = clap::value_parser!(u32).range(1..), help_heading = QUERY)]
+    pub jobs: Option<u32>,
+
+    /// Query at most N drives at once per storage controller (grouped by PCI address in
+    /// sysfs), running different controllers in parallel; replaces --jobs
+    #[arg(long, value_name = "N", env = "HDDTEMP_PARALLEL_PER_CONTROLLER", conflicts_with = "jobs",
+          value_parser = clap::value_parser!(u32).range(1..), help_heading = QUERY)]
+    pub parallel_per_controller: Option<u32>,
+
+    /// Skip drives with no I/O for at least SECONDS, so routine polling does not wake
+    /// drives that have likely spun down (status SKIP); needs a previous run to compare against
+    #[arg(long, value_name = "SECONDS", env = "HDDTEMP_SKIP_IDLE", help_heading = QUERY)]
+    pub skip_idle: Option<u64>,
+
+    /// Query each drive at most once per SECONDS, even across separate invocations: a drive
+    /// read less than SECONDS ago with the same options reports that reading again
+    /// (cached_seconds in JSON), kept in readings.json next to --state-file; 0 disables
+    #[arg(long, value_name = "SECONDS", default_value_t = 60, env = "HDDTEMP_MIN_INTERVAL",
+          help_heading = QUERY)]
+    pub min_interval: u64,
+
+    /// Write a timestamped JSON report (hddtemp-YYYYMMDDTHHMMSSZ.json) to DIR on every scan
+    #[arg(long, value_name = "DIR", env = "HDDTEMP_REPORT_DIR", help_heading = CONFIG)]
+    pub report_dir: Option<PathBuf>,
+
+    /// With --report-dir, keep only the newest N reports (at least 1, the report just written)
+    #[arg(long, value_name = "N", requires = "report_dir", env = "HDDTEMP_REPORT_KEEP",
+          value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+          help_heading = CONFIG)]
+    pub report_keep: Option<usize>,
+
+    /// Append every scan's readings to the CSV file FILE, one row per drive with a leading
+    /// timestamp column; refuses to append if the file's header has different columns
+    #[arg(long, value_name = "FILE", env = "HDDTEMP_LOG_CSV", help_heading = CONFIG)]
+    pub log_csv: Option<PathBuf>,
+
+    /// With --log-csv, append even if the existing header does not match this run's columns
+    #[arg(long, requires = "log_csv", env = "HDDTEMP_FORCE_APPEND", help_heading = CONFIG)]
+    pub force_append: bool,
+
+    /// Exit without querying if another instance is already running (see --lock-file)
+    #[arg(long, env = "HDDTEMP_ONCE", help_heading = CONFIG)]
+    pub once: bool,
+
+    /// With --once, wait for the other instance to finish instead of exiting
+    #[arg(long, requires = "once", env = "HDDTEMP_LOCK_WAIT", help_heading = CONFIG)]
+    pub lock_wait: bool,
+
+    /// Lock file used by --once; holds the pid of the running instance
+    #[arg(long, value_name = "FILE", default_value = lock::DEFAULT_LOCK_FILE,
+          env = "HDDTEMP_LOCK_FILE", help_heading = CONFIG)]
+    pub lock_file: PathBuf,
+
+    /// Remember which smartctl device type worked for each drive in FILE and try it first on
+    /// later runs; rebuilt when the set of drives changes or it is older than --scan-cache-ttl
+    #[arg(long, value_name = "FILE", env = "HDDTEMP_SCAN_CACHE", help_heading = CONFIG)]
+    pub scan_cache: Option<PathBuf>,
+
+    /// Maximum age of the --scan-cache file in seconds
+    #[arg(long, value_name = "SECONDS", default_value_t = 86400, requires = "scan_cache",
+          env = "HDDTEMP_SCAN_CACHE_TTL", help_heading = CONFIG)]
+    pub scan_cache_ttl: u64,
+
+    /// File used to remember per-drive state between runs
+    #[arg(long, value_name = "FILE", default_value = state::DEFAULT_STATE_FILE,
+          env = "HDDTEMP_STATE_FILE", help_heading = CONFIG)]
+    pub state_file: PathBuf,
+
+    /// Mark drives at or above this temperature (°C) as WARN; exit code 1
+    #[arg(long, value_name = "CELSIUS", env = "HDDTEMP_WARN", help_heading = THRESHOLD)]
+    pub warn: Option<f64>,
+
+    /// Mark drives at or above this temperature (°C) as CRIT; exit code 2
+    #[arg(long, value_name = "CELSIUS", env = "HDDTEMP_CRIT", help_heading = THRESHOLD)]
+    pub crit: Option<f64>,
+
+    /// Without --crit, drives whose model family has a known rated maximum temperature (see
+    /// --rated-table) get CRIT at that maximum minus CELSIUS
+    #[arg(long, value_name = "CELSIUS", default_value_t = 5.0, env = "HDDTEMP_RATED_MARGIN",
+          help_heading = THRESHOLD)]
+    pub rated_margin: f64,
+
+    /// Ambient temperature (°C); adds each drive's difference from it (DELTA_AMB column,
+    /// delta_ambient_c in JSON), which points at airflow problems better than absolute readings
+    #[arg(long, value_name = "CELSIUS", env = "HDDTEMP_AMBIENT", help_heading = THRESHOLD)]
+    pub ambient: Option<f64>,
+
+    /// Shell command to run when any drive reaches CRIT; the devices are passed in
+    /// HDDTEMP_CRIT_DEVICES (space-separated)
+    #[arg(long, value_name = "COMMAND", env = "HDDTEMP_ON_CRIT", help_heading = THRESHOLD)]
+    pub on_crit: Option<String>,
+
+    /// Run --on-crit for a drive at most once per SECONDS, even across separate runs (e.g.
+    /// from cron); last alert times are kept in alerts.json next to --state-file
+    #[arg(long, value_name = "SECONDS", requires = "on_crit", env = "HDDTEMP_ALERT_COOLDOWN",
+          help_heading = THRESHOLD)]
+    pub alert_cooldown: Option<u64>,
+
+    /// After the output, print on stderr which drives caused the exit code, e.g.
+    /// "exit=2 because /dev/sdc=61°C exceeds crit=55°C"
+    #[arg(long, env = "HDDTEMP_EXPLAIN_EXIT", help_heading = THRESHOLD)]
+    pub explain_exit: bool,
+
+    /// Suppress --on-crit and the non-zero exit code until this Unix timestamp
+    /// (e.g. `date -d '+2 hours' +%s`); readings are still reported
+    #[arg(long, value_name = "UNIX_SECONDS", env = "HDDTEMP_QUIET_UNTIL", help_heading = THRESHOLD)]
+    pub quiet_until: Option<u64>,
+
+    /// Suppress --on-crit and the non-zero exit code while FILE exists
+    #[arg(long, value_name = "FILE", env = "HDDTEMP_MAINTENANCE_FILE", help_heading = THRESHOLD)]
+    pub maintenance_file: Option<PathBuf>,
+
+    /// Text shown in the table and daemon output when a drive's vendor cannot be identified
+    /// (may be empty); JSON always uses null
+    #[arg(long, value_name = "TEXT", default_value = "Unknown Vendor",
+          env = "HDDTEMP_UNKNOWN_VENDOR", help_heading = OUTPUT)]
+    pub unknown_vendor: String,
+
+    /// Text shown in the table and daemon output when a drive's model cannot be identified
+    /// (may be empty); JSON always uses null
+    #[arg(long, value_name = "TEXT", default_value = "Unknown Model",
+          env = "HDDTEMP_UNKNOWN_MODEL", help_heading = OUTPUT)]
+    pub unknown_model: String,
+
+    /// TOML (or .json) config file defining custom columns
+    #[arg(long, value_name = "FILE", env = "HDDTEMP_CONFIG", help_heading = CONFIG)]
+    pub config: Option<PathBuf>,
+
+    /// TOML file mapping drives to notes shown in a NOTE column, e.g. "/dev/sda" = "Bay 3";
+    /// keys may also be a serial number or WWN
+    #[arg(long, value_name = "FILE", env = "HDDTEMP_ANNOTATIONS", help_heading = CONFIG)]
+    pub annotations: Option<PathBuf>,
+
+    /// Report DEVICE as NAME in every output instead of its kernel path, e.g. /dev/sda=cache0;
+    /// may be repeated. JSON keeps the path in device_path
+    #[arg(long, value_name = "DEVICE=NAME", value_parser = parse_label, value_delimiter = ',',
+          env = "HDDTEMP_LABEL", help_heading = CONFIG)]
+    pub label: Vec<(String, String)>,
+
+    /// Name this machine as NAME (default: the system hostname): the top-level host of JSON
+    /// and YAML reports, and the host label of local drives in OpenMetrics output
+    #[arg(long, value_name = "NAME", env = "HDDTEMP_HOSTNAME", help_heading = CONFIG)]
+    pub hostname: Option<String>,
+
+    /// TOML file mapping model family prefixes to rated maximum temperatures (°C), e.g.
+    /// "Seagate Exos" = 60; merged over the built-in table, taking precedence
+    #[arg(long, value_name = "FILE", env = "HDDTEMP_RATED_TABLE", help_heading = CONFIG)]
+    pub rated_table: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct ReadArgs {
+    #[command(flatten)]
+    pub query: QueryArgs,
+
+    /// Also write the results as JSON to FILE; with "-", JSON goes to stdout and the table to stderr
+    #[arg(long, value_name = "FILE|-", env = "HDDTEMP_ALSO_JSON", help_heading = OUTPUT)]
+    pub also_json: Option<String>,
+
+    /// Indent JSON output instead of writing it compactly
+    #[arg(long, env = "HDDTEMP_PRETTY", help_heading = OUTPUT)]
+    pub pretty: bool,
+
+    /// Save this run's readings to FILE as a JSON report, for a later --compare
+    #[arg(long, value_name = "FILE", env = "HDDTEMP_SNAPSHOT", help_heading = OUTPUT)]
+    pub snapshot: Option<PathBuf>,
+
+    /// Add BEFORE and DELTA columns comparing each drive with a saved JSON report (from
+    /// --snapshot, --also-json or --report-dir); drives are matched by serial, WWN or device
+    #[arg(long, value_name = "FILE", env = "HDDTEMP_COMPARE", help_heading = OUTPUT)]
+    pub compare: Option<PathBuf>,
+
+    /// Truncate the MODEL column to N characters with an ellipsis (table only)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..),
+          env = "HDDTEMP_MAX_MODEL_WIDTH", help_heading = OUTPUT)]
+    pub max_model_width: Option<u32>,
+
+    /// Add a TYPE column (HDD, SSD or NVMe, from the drive's rotation rate and protocol)
+    #[arg(long, env = "HDDTEMP_SHOW_TYPE", help_heading = OUTPUT)]
+    pub show_type: bool,
+
+    /// Add a CAPACITY column with each drive's size in binary units (e.g. 3.6T); JSON always
+    /// has capacity_bytes
+    #[arg(long, env = "HDDTEMP_SHOW_CAPACITY", help_heading = OUTPUT)]
+    pub show_capacity: bool,
+
+    /// Add a RATED column with the rated maximum temperature of the drive's model family, when
+    /// known; JSON always has rated_max_c
+    #[arg(long, env = "HDDTEMP_SHOW_RATED", help_heading = OUTPUT)]
+    pub show_rated: bool,
+
+    /// Table layout
+    #[arg(long, value_enum, default_value_t = TableStyle::Default, env = "HDDTEMP_TABLE_STYLE",
+          help_heading = OUTPUT)]
+    pub table_style: TableStyle,
+
+    /// Table layout: "records" prints one block per drive ("  TEMP: 38°C" lines); "auto"
+    /// switches to records when the table is wider than the terminal
+    #[arg(long, value_enum, default_value_t = Layout::Auto, env = "HDDTEMP_LAYOUT",
+          help_heading = OUTPUT)]
+    pub layout: Layout,
+
+    /// Shorthand for --table-style compact
+    #[arg(long, conflicts_with = "table_style", env = "HDDTEMP_COMPACT", help_heading = OUTPUT)]
+    pub compact: bool,
+
+    /// Add a BUS column showing where each drive is attached (its /dev/disk/by-path name,
+    /// e.g. pci-0000:03:00.0-sas-phy2-lun-0)
+    #[arg(long, env = "HDDTEMP_SHOW_BUS", help_heading = OUTPUT)]
+    pub show_bus: bool,
+
+    /// Show only the N hottest drives, sorted by temperature (descending); drives without a
+    /// reading are left out of the ranking and listed on stderr
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..),
+          conflicts_with_all = ["max_only", "zabbix_item", "zabbix_discovery"],
+          env = "HDDTEMP_TOP", help_heading = OUTPUT)]
+    pub top: Option<u32>,
+
+    /// Collapse drives whose rows are identical apart from the device name into one row,
+    /// with the count in the DEVICE column (e.g. "20×"), so outliers in large arrays stand out
+    #[arg(long, env = "HDDTEMP_GROUP_IDENTICAL", help_heading = OUTPUT)]
+    pub group_identical: bool,
+
+    /// Do not print the "Readings taken at <UTC time>" line above the table
+    #[arg(long, env = "HDDTEMP_NO_TIMESTAMP", help_heading = OUTPUT)]
+    pub no_timestamp: bool,
+
+    /// Print a single line of device=temp pairs (e.g. "sda=38 nvme0n1=42") instead of the table
+    #[arg(long, env = "HDDTEMP_FLAT", help_heading = OUTPUT)]
+    pub flat: bool,
+
+    /// With --flat, show unreadable drives as device=NA instead of omitting them
+    #[arg(long, requires = "flat", env = "HDDTEMP_FLAT_NA", help_heading = OUTPUT)]
+    pub flat_na: bool,
+
+    /// Print only the hottest drive's temperature as a bare number (e.g. "46"); unreadable
+    /// drives are ignored
+    #[arg(long, conflicts_with = "flat", env = "HDDTEMP_MAX_ONLY", help_heading = OUTPUT)]
+    pub max_only: bool,
+
+    /// Print only a one-line summary ("8 disks, 8 readable, max 46°C, 1 warn, 0 crit") instead
+    /// of per-drive output; the exit code is unchanged
+    #[arg(long, conflicts_with_all = ["flat", "max_only", "top", "zabbix_discovery", "zabbix_item",
+                                      "format_preset"],
+          env = "HDDTEMP_SUMMARY_ONLY", help_heading = OUTPUT)]
+    pub summary_only: bool,
+
+    /// Print Zabbix low-level discovery JSON ({"data":[{"{#DEVICE}":...,"{#MODEL}":...}]})
+    /// for the drives that could be queried
+    #[arg(long, conflicts_with_all = ["flat", "max_only", "zabbix_item"],
+          env = "HDDTEMP_ZABBIX_DISCOVERY", help_heading = OUTPUT)]
+    pub zabbix_discovery: bool,
+
+    /// Query only DEVICE and print its temperature as a bare number, for Zabbix item polling
+    #[arg(long, value_name = "DEVICE", conflicts_with_all = ["flat", "max_only", "devices"],
+          env = "HDDTEMP_ZABBIX_ITEM", help_heading = OUTPUT)]
+    pub zabbix_item: Option<String>,
+
+    /// Print in the format of another tool instead of the table; "hddtemp" prints
+    /// "/dev/sda: MODEL: 38°C" per drive, like the original hddtemp binary
+    #[arg(long, value_enum, value_name = "PRESET",
+          conflicts_with_all = ["flat", "max_only", "zabbix_discovery", "zabbix_item"],
+          env = "HDDTEMP_FORMAT_PRESET", help_heading = OUTPUT)]
+    pub format_preset: Option<FormatPreset>,
+
+    /// Show temperatures in the table in Fahrenheit (machine-readable output always has both units)
+    #[arg(short = 'F', long, env = "HDDTEMP_FAHRENHEIT", help_heading = OUTPUT)]
+    pub fahrenheit: bool,
+
+    /// Print the value at PATH in each drive's smartctl JSON instead of the table, to find paths
+    /// for custom columns; PATH is a JSON Pointer ("/temperature/current") or a custom-column
+    /// path ("ata_smart_attributes.table[id=194].raw.value")
+    #[arg(long, value_name = "PATH", env = "HDDTEMP_PROBE", help_heading = OUTPUT)]
+    pub probe: Option<String>,
+
+    /// With --probe, read the JSON from FILE (e.g. saved `smartctl --json -a` output) instead of
+    /// querying drives
+    #[arg(long, value_name = "FILE", requires = "probe", env = "HDDTEMP_FROM_FILE",
+          help_heading = OUTPUT)]
+    pub from_file: Option<PathBuf>,
+
+    /// Show table temperatures in both units, e.g. "38°C / 100°F" (Fahrenheit first with -F)
+    #[arg(long, env = "HDDTEMP_SHOW_BOTH_UNITS", help_heading = OUTPUT)]
+    pub show_both_units: bool,
+
+    /// Run the full scan RUNS times and print timing statistics instead of the table
+    #[arg(long, value_name = "RUNS", value_parser = clap::value_parser!(u32).range(1..),
+          env = "HDDTEMP_BENCH", help_heading = OUTPUT)]
+    pub bench: Option<u32>,
+
+    /// Rescan and redraw every SECONDS until interrupted; the table gains an AGE column with the
+    /// time since each drive last gave a reading
+    #[arg(long, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..),
+          conflicts_with_all = ["also_json", "snapshot", "bench", "probe", "zabbix_item",
+                                "zabbix_discovery"],
+          env = "HDDTEMP_WATCH", help_heading = OUTPUT)]
+    pub watch: Option<u64>,
+
+    /// With --watch, print the full table once and then only one line per drive whose reading
+    /// or status changed, e.g. "14:03:22 /dev/sdc 45→47", instead of redrawing every cycle
+    #[arg(long, requires = "watch", env = "HDDTEMP_WATCH_DIFF", help_heading = OUTPUT)]
+    pub watch_diff: bool,
+
+    /// Number of decimal places shown for temperatures in the table and --flat output
+    #[arg(long, value_name = "N", default_value_t = 0, env = "HDDTEMP_PRECISION",
+          help_heading = OUTPUT)]
+    pub precision: usize,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct ExportArgs {
+    #[command(flatten)]
+    pub query: QueryArgs,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = ExportFormat::Json, env = "HDDTEMP_FORMAT",
+          help_heading = OUTPUT)]
+    pub format: ExportFormat,
+
+    /// Indent JSON output instead of writing it compactly
+    #[arg(long, env = "HDDTEMP_PRETTY", help_heading = OUTPUT)]
+    pub pretty: bool,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct DaemonArgs {
+    #[command(flatten)]
+    pub query: QueryArgs,
+
+    /// Socket address to listen on, e.g. 0.0.0.0:7634 or [::1]:7634; [::]:PORT accepts both
+    /// IPv6 and IPv4 where the system allows dual-stack sockets
+    #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:7634", env = "HDDTEMP_LISTEN",
+          help_heading = DAEMON)]
+    pub listen: SocketAddr,
+
+    /// Override the port of --listen
+    #[arg(short, long, env = "HDDTEMP_PORT", help_heading = DAEMON)]
+    pub port: Option<u16>,
+
+    /// Seconds between background scans
+    #[arg(long, value_name = "SECONDS", default_value_t = 60, env = "HDDTEMP_INTERVAL",
+          value_parser = clap::value_parser!(u64).range(1..), help_heading = DAEMON)]
+    pub interval: u64,
+
+    /// Add a random delay of up to this many seconds to each background scan interval, so hosts
+    /// started together do not all query their drives at the same moment
+    #[arg(long, value_name = "SECONDS", default_value_t = 0, env = "HDDTEMP_POLL_JITTER",
+          help_heading = DAEMON)]
+    pub poll_jitter: u64,
+
+    /// Field separator used in the daemon output
+    #[arg(short, long, default_value_t = '|', env = "HDDTEMP_SEPARATOR", help_heading = DAEMON)]
+    pub separator: char,
+}
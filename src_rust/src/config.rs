@@ -0,0 +1,134 @@
+// 配置文件（TOML 或 JSON）解析，以及自定义列使用的 JSON 路径求值
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// 配置文件内容
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    pub columns: Vec<ColumnDef>,
+}
+
+// 一个自定义列：列名以及在 smartctl JSON 中的路径
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ColumnDef {
+    pub name: String,
+    pub path: String,
+}
+
+// 路径中的一段
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    // 对象字段，如 temperature
+    Key(String),
+    // 数组下标，如 [0]
+    Index(usize),
+    // 数组中第一个满足 field=value 的元素，如 [name=Temperature_Celsius]
+    Match(String, String),
+}
+
+// 编译后的 JSON 路径，如 "ata_smart_attributes.table[name=Reallocated_Sector_Ct].raw.value"
+#[derive(Debug, Clone)]
+pub struct JsonPath(Vec<Segment>);
+
+impl JsonPath {
+    pub fn parse(path: &str) -> Result<Self, String> {
+        let mut segments = Vec::new();
+        for part in path.split('.') {
+            let (key, mut rest) = match part.find('[') {
+                Some(i) => (&part[..i], &part[i..]),
+                None => (part, ""),
+            };
+            if !key.is_empty() {
+                segments.push(Segment::Key(key.to_string()));
+            } else if rest.is_empty() {
+                return Err(format!("Empty segment in path: {path}"));
+            }
+
+            while !rest.is_empty() {
+                let end = rest
+                    .find(']')
+                    .filter(|_| rest.starts_with('['))
+                    .ok_or_else(|| format!("Malformed brackets in path: {path}"))?;
+                let selector = &rest[1..end];
+                let segment = match selector.split_once('=') {
+                    Some((field, value)) => Segment::Match(field.to_string(), value.to_string()),
+                    None => Segment::Index(
+                        selector
+                            .parse()
+                            .map_err(|_| format!("Invalid index [{selector}] in path: {path}"))?,
+                    ),
+                };
+                segments.push(segment);
+                rest = &rest[end + 1..];
+            }
+        }
+        Ok(JsonPath(segments))
+    }
+
+    // 在 JSON 树上求值，路径不存在时返回 None
+    pub fn evaluate<'a>(&self, root: &'a Value) -> Option<&'a Value> {
+        self.0
+            .iter()
+            .try_fold(root, |value, segment| match segment {
+                Segment::Key(key) => value.get(key),
+                Segment::Index(index) => value.get(index),
+                Segment::Match(field, expected) => {
+                    value
+                        .as_array()?
+                        .iter()
+                        .find(|item| match &item[field.as_str()] {
+                            Value::String(s) => s == expected,
+                            other => expected.parse::<Value>().is_ok_and(|v| v == *other),
+                        })
+                }
+            })
+    }
+}
+
+// 读取配置文件，按扩展名区分 JSON 与 TOML（默认 TOML）
+pub fn load(path: &Path) -> io::Result<Config> {
+    let content = fs::read_to_string(path)?;
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    let parsed: Result<Config, String> = if is_json {
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    } else {
+        toml::from_str(&content).map_err(|e| e.to_string())
+    };
+    let config = parsed.map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid config {}: {e}", path.display()),
+        )
+    })?;
+
+    // 提前校验路径，避免在渲染时才报错
+    for column in &config.columns {
+        JsonPath::parse(&column.path).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Column {:?}: {e}", column.name),
+            )
+        })?;
+    }
+    Ok(config)
+}
+
+// 读取 --annotations 映射文件：键为设备名（可省略 /dev/）、序列号或 WWN，值为备注
+pub fn load_annotations(path: &Path) -> io::Result<BTreeMap<String, String>> {
+    let content = fs::read_to_string(path)?;
+    toml::from_str(&content).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid annotations file {}: {e}", path.display()),
+        )
+    })
+}
@@ -0,0 +1,138 @@
+// 兼容 hddtemp 的 TCP 守护进程：后台定时扫描，客户端连接时返回最近一次结果
+use crate::alert;
+use crate::cli::DaemonArgs;
+use crate::output::{OutputFormat, Placeholders};
+use crate::{DiskInfo, Scanner};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+// 按 hddtemp 协议格式化结果：每个设备为 |设备|型号|温度|单位|，依次拼接；协议只支持整数温度。
+// 没有读数的设备不省略，按 hddtemp 的约定以 ERR（查询失败）、SLP（休眠，被 --skip-idle 或 --power-mode 跳过）
+// 或 NA（无温度传感器）代替温度，单位为 *；只有查询失败时型号为 ???
+struct HddtempFormat<'a> {
+    separator: char,
+    placeholders: Placeholders<'a>,
+}
+
+impl OutputFormat for HddtempFormat<'_> {
+    fn render(&self, results: &[DiskInfo], out: &mut dyn Write) -> io::Result<()> {
+        for info in results {
+            let (model, temp, unit) = match info.temperature {
+                Some(temp) => (self.placeholders.model(info), format!("{temp:.0}"), "C"),
+                None if info.error.is_some() => ("???", "ERR".to_string(), "*"),
+                None if info.idle_seconds.is_some() || info.status == "STANDBY" => {
+                    (self.placeholders.model(info), "SLP".to_string(), "*")
+                }
+                None => (self.placeholders.model(info), "NA".to_string(), "*"),
+            };
+            write!(
+                out,
+                "{sep}{}{sep}{model}{sep}{temp}{sep}{unit}{sep}",
+                info.device,
+                sep = self.separator
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// 执行一次扫描，失败时保留上一次的结果
+fn refresh(scanner: &Scanner, response: &RwLock<String>, separator: char) {
+    match scanner.scan() {
+        Ok(results) => {
+            alert::handle(&results, &scanner.query);
+            crate::archive_report(&results, scanner);
+            let mut formatted = Vec::new();
+            let format = HddtempFormat {
+                separator,
+                placeholders: Placeholders::from_query(&scanner.query),
+            };
+            if let Err(e) = format.render(&results, &mut formatted) {
+                eprintln!("Failed to format response: {e}");
+                return;
+            }
+            if let Ok(mut guard) = response.write() {
+                *guard = String::from_utf8_lossy(&formatted).into_owned();
+            }
+        }
+        Err(e) => eprintln!("Scan failed: {e}"),
+    }
+}
+
+// --poll-jitter：0 到 max 之间的随机时长。每个 RandomState 都带有随机的哈希种子，足以错开各主机的扫描
+fn jitter(max: Duration) -> Duration {
+    if max.is_zero() {
+        return max;
+    }
+    let mut hasher = RandomState::new().build_hasher();
+    if let Ok(now) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+        hasher.write_u128(now.as_nanos());
+    }
+    max.mul_f64(hasher.finish() as f64 / u64::MAX as f64)
+}
+
+pub fn run(scanner: Scanner, args: &DaemonArgs) -> io::Result<()> {
+    let mut addr = args.listen;
+    if let Some(port) = args.port {
+        addr.set_port(port);
+    }
+    let listener = TcpListener::bind(addr)
+        .map_err(|e| io::Error::new(e.kind(), format!("Cannot listen on {addr}: {e}")))?;
+    let response = Arc::new(RwLock::new(String::new()));
+
+    // 先同步扫描一次，保证第一个客户端就能拿到数据
+    refresh(&scanner, &response, args.separator);
+
+    let interval = Duration::from_secs(args.interval);
+    let max_jitter = Duration::from_secs(args.poll_jitter);
+    let separator = args.separator;
+    let background = Arc::clone(&response);
+    thread::spawn(move || loop {
+        thread::sleep(interval + jitter(max_jitter));
+        refresh(&scanner, &background, separator);
+    });
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(mut stream) => {
+                let body = response.read().map(|r| r.clone()).unwrap_or_default();
+                if let Err(e) = stream.write_all(body.as_bytes()) {
+                    eprintln!("Failed to write to client: {e}");
+                }
+            }
+            Err(e) => eprintln!("Failed to accept connection: {e}"),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::sample_disks;
+
+    #[test]
+    fn hddtemp_protocol_records() {
+        let format = HddtempFormat {
+            separator: '|',
+            placeholders: Placeholders {
+                vendor: "Unknown Vendor",
+                model: "Unknown Model",
+            },
+        };
+        let mut out = Vec::new();
+        format.render(&sample_disks(), &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "|/dev/sda|ST4000VN008-2DR166|36|C|\
+             |/dev/sdb|WDC WD40EFRX-68N32N0|SLP|*|\
+             |/dev/sdc|Unknown Model|NA|*|\
+             |/dev/sdd|???|ERR|*|"
+        );
+    }
+}
@@ -0,0 +1,63 @@
+// 内核 drivetemp 模块的温度读取：直接读取 hwmon，不调用 smartctl，也不会唤醒休眠的硬盘
+use crate::hwmon::{self, read_attr};
+use crate::{DiskReading, TempSource};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const HWMON_CLASS: &str = "/sys/class/hwmon";
+
+// drivetemp 的 hwmonN/device 指向磁盘的 SCSI 设备（如 .../host0/target0:0:0/0:0:0:0），
+// 其下的 block/ 目录给出对应的块设备名；以此反查，而不是猜测 hwmon 编号与盘符的关系
+fn serves_block_device(hwmon: &Path, name: &str) -> bool {
+    fs::read_dir(hwmon.join("device").join("block")).is_ok_and(|entries| {
+        entries
+            .filter_map(Result::ok)
+            .any(|entry| entry.file_name().to_str() == Some(name))
+    })
+}
+
+// 查找为 device 报告温度的 drivetemp 传感器目录
+fn find_sensor(device: &str) -> Option<PathBuf> {
+    let name = device.trim_start_matches("/dev/");
+    hwmon::sorted_entries(Path::new(HWMON_CLASS))?
+        .into_iter()
+        .filter(|hwmon| read_attr(&hwmon.join("name")).as_deref() == Some("drivetemp"))
+        .find(|hwmon| serves_block_device(hwmon, name))
+}
+
+// SCSI 设备的 vendor/model 属性；libata 的盘 vendor 固定为 "ATA"，不代表真实厂商。
+// 只读 sysfs，不会唤醒休眠的硬盘
+pub fn identity(device: &str) -> (Option<String>, Option<String>) {
+    let dir = Path::new("/sys/block")
+        .join(device.trim_start_matches("/dev/"))
+        .join("device");
+    let vendor = read_attr(&dir.join("vendor")).filter(|v| v != "ATA");
+    (vendor, read_attr(&dir.join("model")))
+}
+
+pub fn get_disk_info_and_temperature(device: &str) -> io::Result<DiskReading> {
+    let sensor = find_sensor(device).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No drivetemp sensor for {device} (is the drivetemp module loaded?)"),
+        )
+    })?;
+    let (temperature, source) = hwmon::read_sensor(&sensor).ok_or_else(|| {
+        io::Error::other(format!(
+            "drivetemp sensor {} has no readable temperature",
+            sensor.display()
+        ))
+    })?;
+    let (vendor, model) = identity(device);
+    Ok(DiskReading {
+        vendor,
+        model,
+        temperature: Some(temperature),
+        temperature_source: Some(TempSource::Sysfs),
+        samples: Vec::new(),
+        raw: None,
+        unit_correction: None,
+        hwmon_source: Some(source),
+    })
+}
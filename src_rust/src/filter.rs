@@ -0,0 +1,286 @@
+// --filter 表达式：field op value，可用 and/or 组合（and 优先），如 "temp > 50 and vendor == Seagate"
+use crate::DiskInfo;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Temp,
+    Device,
+    Vendor,
+    Model,
+    Status,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+#[derive(Debug, Clone)]
+enum Value {
+    Number(f64),
+    Text(String),
+}
+
+#[derive(Debug, Clone)]
+struct Condition {
+    field: Field,
+    op: Op,
+    value: Value,
+}
+
+// 词法单元；带引号的字符串只作为值，即使内容是 and/or 也不会被当作连接词
+#[derive(Debug, Clone, PartialEq)]
+struct Token {
+    text: String,
+    quoted: bool,
+}
+
+impl Token {
+    fn word(text: String) -> Self {
+        Token {
+            text,
+            quoted: false,
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        !self.quoted && self.text.eq_ignore_ascii_case(keyword)
+    }
+}
+
+// 按 or 分组的 and 条件列表（析取范式）
+#[derive(Debug, Clone)]
+pub struct Filter(Vec<Vec<Condition>>);
+
+// 切分为单词、带引号的字符串和比较运算符
+fn tokenize(expr: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = expr.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' || c == '\'' {
+            chars.next();
+            let mut text = String::new();
+            let mut closed = false;
+            for ch in chars.by_ref() {
+                if ch == c {
+                    closed = true;
+                    break;
+                }
+                text.push(ch);
+            }
+            if !closed {
+                return Err(format!("unterminated {c} quote"));
+            }
+            tokens.push(Token { text, quoted: true });
+        } else if "=!<>".contains(c) {
+            let mut op = String::new();
+            while let Some(&ch) = chars.peek().filter(|ch| "=!<>".contains(**ch)) {
+                op.push(ch);
+                chars.next();
+            }
+            tokens.push(Token::word(op));
+        } else {
+            let mut word = String::new();
+            while let Some(&ch) = chars
+                .peek()
+                .filter(|ch| !ch.is_whitespace() && !"=!<>\"'".contains(**ch))
+            {
+                word.push(ch);
+                chars.next();
+            }
+            tokens.push(Token::word(word));
+        }
+    }
+    if tokens.is_empty() {
+        return Err("empty expression".to_string());
+    }
+    Ok(tokens)
+}
+
+fn parse_condition(tokens: &[Token]) -> Result<Condition, String> {
+    let [field, op, value] = tokens else {
+        let text: Vec<&str> = tokens.iter().map(|t| t.text.as_str()).collect();
+        return Err(format!(
+            "expected \"field op value\", got {:?}",
+            text.join(" ")
+        ));
+    };
+    let (field, op, value) = (&field.text, &op.text, &value.text);
+    let field = match field.to_lowercase().as_str() {
+        "temp" | "temperature" => Field::Temp,
+        "device" => Field::Device,
+        "vendor" => Field::Vendor,
+        "model" => Field::Model,
+        "status" => Field::Status,
+        other => {
+            return Err(format!(
+                "unknown field {other:?} (expected temp, device, vendor, model or status)"
+            ))
+        }
+    };
+    let op = match op.as_str() {
+        "==" | "=" => Op::Eq,
+        "!=" => Op::Ne,
+        ">" => Op::Gt,
+        ">=" => Op::Ge,
+        "<" => Op::Lt,
+        "<=" => Op::Le,
+        other => return Err(format!("unknown operator {other:?}")),
+    };
+    let value = if field == Field::Temp {
+        Value::Number(
+            value
+                .parse()
+                .map_err(|_| format!("temp must be compared with a number, got {value:?}"))?,
+        )
+    } else if matches!(op, Op::Eq | Op::Ne) {
+        Value::Text(value.clone())
+    } else {
+        return Err("text fields only support == and !=".to_string());
+    };
+    Ok(Condition { field, op, value })
+}
+
+impl Filter {
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let tokens = tokenize(expr)?;
+        let mut groups = Vec::new();
+        for group in tokens.split(|t| t.is_keyword("or")) {
+            let conditions = group
+                .split(|t| t.is_keyword("and"))
+                .map(parse_condition)
+                .collect::<Result<Vec<_>, _>>()?;
+            groups.push(conditions);
+        }
+        Ok(Filter(groups))
+    }
+
+    pub fn matches(&self, info: &DiskInfo) -> bool {
+        self.0
+            .iter()
+            .any(|group| group.iter().all(|condition| condition.matches(info)))
+    }
+}
+
+impl Condition {
+    fn matches(&self, info: &DiskInfo) -> bool {
+        let text = match self.field {
+            Field::Temp => {
+                // 没有温度的设备不满足任何温度条件
+                let (Some(temp), Value::Number(limit)) = (info.temperature, &self.value) else {
+                    return false;
+                };
+                return match self.op {
+                    Op::Eq => temp == *limit,
+                    Op::Ne => temp != *limit,
+                    Op::Gt => temp > *limit,
+                    Op::Ge => temp >= *limit,
+                    Op::Lt => temp < *limit,
+                    Op::Le => temp <= *limit,
+                };
+            }
+            Field::Device => &info.device,
+            Field::Vendor => info.vendor.as_deref().unwrap_or_default(),
+            Field::Model => info.model.as_deref().unwrap_or_default(),
+            Field::Status => &info.status,
+        };
+        let Value::Text(expected) = &self.value else {
+            return false;
+        };
+        // 设备名可省略 /dev/ 前缀，文本比较不区分大小写
+        let equal = text.eq_ignore_ascii_case(expected)
+            || (self.field == Field::Device
+                && text
+                    .trim_start_matches("/dev/")
+                    .eq_ignore_ascii_case(expected));
+        equal == (self.op == Op::Eq)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn disk(device: &str, vendor: &str, temp: Option<f64>) -> DiskInfo {
+        DiskInfo {
+            device: device.to_string(),
+            vendor: Some(vendor.to_string()),
+            temperature: temp,
+            status: "OK".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn texts(expr: &str) -> Vec<String> {
+        tokenize(expr)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.text)
+            .collect()
+    }
+
+    #[test]
+    fn tokenize_splits_operators_and_quotes() {
+        assert_eq!(texts("temp>=50"), ["temp", ">=", "50"]);
+        assert_eq!(
+            texts("model == 'WDC WD40' or vendor!=\"x y\""),
+            ["model", "==", "WDC WD40", "or", "vendor", "!=", "x y"]
+        );
+        assert!(tokenize("   ").is_err());
+        assert!(tokenize("model == 'WDC").is_err());
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        // 即 (temp > 50 and vendor == Seagate) or device == sdb
+        let filter = Filter::parse("temp > 50 and vendor == Seagate or device == sdb").unwrap();
+        assert!(filter.matches(&disk("/dev/sda", "Seagate", Some(55.0))));
+        assert!(!filter.matches(&disk("/dev/sda", "Seagate", Some(40.0))));
+        assert!(!filter.matches(&disk("/dev/sdc", "WDC", Some(55.0))));
+        assert!(filter.matches(&disk("/dev/sdb", "WDC", Some(30.0))));
+    }
+
+    #[test]
+    fn quoted_keywords_are_values() {
+        let filter = Filter::parse("vendor == 'and' or vendor == \"OR\"").unwrap();
+        assert!(filter.matches(&disk("/dev/sda", "and", None)));
+        assert!(filter.matches(&disk("/dev/sda", "or", None)));
+        assert!(!filter.matches(&disk("/dev/sda", "Seagate", None)));
+    }
+
+    #[test]
+    fn matches_text_and_missing_temperature() {
+        let filter = Filter::parse("DEVICE = sda and Vendor != wdc").unwrap();
+        assert!(filter.matches(&disk("/dev/sda", "Seagate", None)));
+        assert!(!filter.matches(&disk("/dev/sda", "WDC", None)));
+        // 没有温度的设备既不满足 temp > x 也不满足 temp <= x
+        let hot = Filter::parse("temp > 40").unwrap();
+        let cool = Filter::parse("temp <= 40").unwrap();
+        let unknown = disk("/dev/sda", "Seagate", None);
+        assert!(!hot.matches(&unknown) && !cool.matches(&unknown));
+    }
+
+    #[test]
+    fn malformed_expressions_are_rejected() {
+        for expr in [
+            "",
+            "temp >",
+            "temp > 50 and",
+            "or temp > 50",
+            "temp > hot",
+            "vendor > Seagate",
+            "size == 1",
+            "temp >> 50",
+            "temp > 50 vendor == x",
+        ] {
+            assert!(Filter::parse(expr).is_err(), "{expr:?} should not parse");
+        }
+    }
+}
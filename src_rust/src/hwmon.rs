@@ -0,0 +1,68 @@
+// 内核 hwmon 温度传感器的读取，并记录读数来自哪个传感器：
+// hwmon 与块设备的对应关系容易出错，输出传感器的 name/label 便于确认读到的不是主板等其他传感器
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// 产生读数的 hwmon 传感器
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HwmonSource {
+    // hwmon 驱动名（hwmonN/name），如 drivetemp、nvme、mmc
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    // 传感器标签（tempN_label），很多驱动不提供
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    // 读取的 tempN_input 文件
+    pub input: String,
+}
+
+// 读取 sysfs 中的单行属性
+pub fn read_attr(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+pub fn sorted_entries(dir: &Path) -> Option<Vec<PathBuf>> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .collect();
+    entries.sort();
+    Some(entries)
+}
+
+// 读取一个 hwmonN 目录中第一个可读的 temp*_input（毫摄氏度转换为摄氏度，保留小数）及其来源
+pub fn read_sensor(hwmon: &Path) -> Option<(f64, HwmonSource)> {
+    let entries = sorted_entries(hwmon)?;
+    let inputs = entries.iter().filter(|p| {
+        p.file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.starts_with("temp") && n.ends_with("_input"))
+    });
+    for input in inputs {
+        let Some(millidegrees) = read_attr(input).and_then(|v| v.parse::<i64>().ok()) else {
+            continue;
+        };
+        let label = input
+            .to_str()
+            .and_then(|p| p.strip_suffix("_input"))
+            .and_then(|stem| read_attr(Path::new(&format!("{stem}_label"))));
+        let source = HwmonSource {
+            name: read_attr(&hwmon.join("name")),
+            label,
+            input: input.display().to_string(),
+        };
+        return Some((millidegrees as f64 / 1000.0, source));
+    }
+    None
+}
+
+// 在设备目录下的 hwmon/ 中查找第一个可读的温度传感器
+pub fn find_temperature(dir: &Path) -> Option<(f64, HwmonSource)> {
+    sorted_entries(&dir.join("hwmon"))?
+        .iter()
+        .find_map(|hwmon| read_sensor(hwmon))
+}
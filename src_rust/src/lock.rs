@@ -0,0 +1,75 @@
+// --once：用 flock 锁住锁文件，防止 cron 重叠时多个实例同时访问 smartctl
+use nix::errno::Errno;
+use nix::fcntl::{Flock, FlockArg};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+// 默认锁文件路径
+pub const DEFAULT_LOCK_FILE: &str = "/run/hddtemp_rust.lock";
+
+// 持有期间其他实例无法获得锁，释放时删除锁文件
+pub struct RunLock {
+    // 只需持有，析构时自动解锁
+    _file: Flock<File>,
+    path: PathBuf,
+}
+
+// 获取锁的结果
+pub enum Acquired {
+    Locked(RunLock),
+    // 锁被其他实例持有，附带锁文件中记录的 pid
+    Busy(Option<u32>),
+}
+
+// 获取锁；wait 为 true 时阻塞等待，否则锁被占用时立即返回 Busy
+pub fn acquire(path: &Path, wait: bool) -> io::Result<Acquired> {
+    let arg = if wait {
+        FlockArg::LockExclusive
+    } else {
+        FlockArg::LockExclusiveNonblock
+    };
+    loop {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))?;
+        let mut file = match Flock::lock(file, arg) {
+            Ok(locked) => locked,
+            Err((_, Errno::EWOULDBLOCK)) => {
+                let pid = fs::read_to_string(path)
+                    .ok()
+                    .and_then(|s| s.trim().parse().ok());
+                return Ok(Acquired::Busy(pid));
+            }
+            Err((_, errno)) => return Err(io::Error::from(errno)),
+        };
+
+        // 上一个持有者可能在我们打开之后删除了锁文件，此时锁住的是已删除的文件，需重试
+        let same_file = match (file.metadata(), fs::metadata(path)) {
+            (Ok(held), Ok(current)) => held.dev() == current.dev() && held.ino() == current.ino(),
+            _ => false,
+        };
+        if !same_file {
+            continue;
+        }
+
+        file.set_len(0)?;
+        writeln!(file, "{}", std::process::id())?;
+        return Ok(Acquired::Locked(RunLock {
+            _file: file,
+            path: path.to_path_buf(),
+        }));
+    }
+}
+
+impl Drop for RunLock {
+    fn drop(&mut self) {
+        // 先删除再随 file 一起解锁，等待者拿到锁后会发现文件已被删除并重试
+        let _ = fs::remove_file(&self.path);
+    }
+}
@@ -1,29 +1,535 @@
-#[macro_use] // 启用 prettytable 宏
-extern crate prettytable;
+mod alert;
+mod bench;
+mod cli;
+mod config;
+mod daemon;
+mod drivetemp;
+mod filter;
+mod hwmon;
+mod lock;
+mod mmc;
+mod nvmecli;
+mod output;
+mod pmem;
+mod pools;
+mod probe;
+mod rated;
+mod remote;
+mod resolve;
+mod smartctl_text;
+mod state;
+#[cfg(test)]
+mod tests;
+#[cfg(feature = "update-check")]
+mod update;
 
-use prettytable::{format, Cell, Row, Table};
+use clap::{Parser, ValueEnum};
+use cli::{
+    Backend, Cli, Commands, DiscoveryArgs, ExportArgs, ExportFormat, ListArgs, QueryArgs, ReadArgs,
+    RepeatAgg, SmartctlArgs,
+};
+
+use output::{
+    CsvFormat, FlatFormat, HddtempLinesFormat, JsonFormat, MaxOnlyFormat, OutputFormat,
+    TableFormat, ZabbixDiscoveryFormat,
+};
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use serde_json::{from_str, Value};
-use std::io;
-use std::process::{Command, Output};
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::io::{self, IsTerminal, Read, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
 
 // 根据设备类型尝试不同的 smartctl 参数
-const DEVICE_TYPES: [&str; 6] = ["", "ata", "sat", "scsi", "nvme", "sata"]; // 增加了"sata"类型
+// 最后的 "auto" 让 smartctl 自行判断，同时也用于交叉验证循环选中的类型
+const DEVICE_TYPES: [&str; 7] = ["", "ata", "sat", "scsi", "nvme", "sata", "auto"]; // 增加了"sata"类型
 
-fn parse_smartctl_output(output: &Output) -> io::Result<(String, String, Option<i64>)> {
-    let output_str = String::from_utf8_lossy(&output.stdout);
+// 同一物理盘（相同 WWN/序列号）的多条路径温度允许的最大差值
+const MULTIPATH_TEMP_TOLERANCE: f64 = 2.0;
+
+// 单个硬盘的查询结果；--remote 从远程主机的 JSON 报告反序列化
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct DiskInfo {
+    // --remote 查询的设备所在的主机，本机设备为 None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    host: Option<String>,
+    // 设置了 --label 时为该名称，否则为设备路径
+    device: String,
+    // --label 替换掉的设备路径
+    #[serde(skip_serializing_if = "Option::is_none")]
+    device_path: Option<String>,
+    // 厂商、型号未能识别时为 None，机器可读输出中为 null
+    vendor: Option<String>,
+    model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    serial: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    wwn: Option<String>,
+    // HDD、SSD 或 NVMe
+    #[serde(skip_serializing_if = "Option::is_none")]
+    drive_type: Option<DriveType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    capacity_bytes: Option<u64>,
+    #[serde(rename = "temperature_c")]
+    temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature_source: Option<TempSource>,
+    // temperature_source 为 sysfs 时读数所来自的 hwmon 传感器
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hwmon_source: Option<hwmon::HwmonSource>,
+    // 设置 --ambient 时高出环境温度的差值
+    #[serde(skip_serializing_if = "Option::is_none")]
+    delta_ambient_c: Option<f64>,
+    // 温度按 0.1°C 或华氏度换算过时注明
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unit_corrected: Option<UnitCorrection>,
+    // --all-temps 下各 ATA 温度属性（194、190）的读数
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    attribute_temperatures_c: BTreeMap<u32, i64>,
+    // --all-sensors 下 NVMe 各温度传感器的读数，键为传感器编号（从 1 起）
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    sensor_temperatures_c: BTreeMap<u32, i64>,
+    // 设备的连接位置（by-path 名称或 sysfs 路径）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bus: Option<String>,
+    // --annotations 中为该设备配置的备注（如机位）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+    // --pools：设备所属的 ZFS 池或 btrfs 文件系统，以及 ZFS 的顶层 vdev
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pool: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vdev: Option<String>,
+    // WHEN_FAILED 非空的 SMART 属性（status 为 PREFAIL）
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    failing_attributes: Vec<String>,
+    // NVMe 健康日志 critical_warning 中置位的各项（status 为 NVME-WARN）
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    nvme_critical_warning: Vec<String>,
+    // --sanity 判定读数可疑的原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    suspect: Option<String>,
+    // SCSI 设备报告的跳闸温度（参考上限）和历史最高温度
+    #[serde(skip_serializing_if = "Option::is_none")]
+    drive_trip_c: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lifetime_max_c: Option<i64>,
+    // 型号家族的额定最高工作温度（内置表或 --rated-table）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rated_max_c: Option<f64>,
+    status: String,
+    // 设置了 --warn/--crit 时的阈值判定结果及所用阈值
+    #[serde(skip_serializing_if = "Option::is_none")]
+    threshold_state: Option<ThresholdState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thresholds: Option<Thresholds>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    // 因 --skip-idle 跳过时设备已空闲的秒数
+    #[serde(skip_serializing_if = "Option::is_none")]
+    idle_seconds: Option<u64>,
+    // 因 --min-interval 沿用之前的读数时，该读数已过去的秒数
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cached_seconds: Option<u64>,
+    // 配置文件中定义的自定义列
+    #[serde(skip_serializing_if = "serde_json::Map::is_empty")]
+    columns: serde_json::Map<String, Value>,
+    // 查询所用的 smartctl 版本，只在报告顶层输出
+    #[serde(skip)]
+    smartctl_version: Option<String>,
+    // smartctl 识别出的设备类型（JSON 的 device.type），写入 --scan-cache
+    #[serde(skip)]
+    smartctl_type: Option<String>,
+    // 查询该设备所花的时间，供 --bench 统计
+    #[serde(skip)]
+    elapsed: Duration,
+    // --watch 下距该设备上一次成功读到温度的时间，从未读到时为 None
+    #[serde(skip)]
+    age: Option<Duration>,
+    // 读数的时间（Unix 秒）：所属扫描开始的时间，--min-interval 沿用的读数为其原本读取的时间
+    #[serde(skip)]
+    taken_at: u64,
+}
+
+// 硬盘类型，由协议和转速判断
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum DriveType {
+    #[serde(rename = "HDD")]
+    Hdd,
+    #[serde(rename = "SSD")]
+    Ssd,
+    #[serde(rename = "NVMe")]
+    Nvme,
+}
+
+impl DriveType {
+    fn as_str(self) -> &'static str {
+        match self {
+            DriveType::Hdd => "HDD",
+            DriveType::Ssd => "SSD",
+            DriveType::Nvme => "NVMe",
+        }
+    }
+}
+
+// 阈值判定结果，没有温度时为 unknown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ThresholdState {
+    Ok,
+    Warn,
+    Crit,
+    Unknown,
+}
+
+// 对某个设备生效的阈值（摄氏度）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+struct Thresholds {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warn_c: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    crit_c: Option<f64>,
+    // crit_c 不是 --crit，而是由额定最高温度减去 --rated-margin 得到
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    crit_from_rated: bool,
+}
+
+impl Thresholds {
+    // 命令行设置的全局阈值，都未设置时返回 None
+    fn from_query(query: &QueryArgs) -> Option<Self> {
+        Self::for_drive(query, None)
+    }
+
+    // 某个设备的阈值：没有 --crit 时以其额定最高温度减去 --rated-margin 作为 crit
+    fn for_drive(query: &QueryArgs, rated_max: Option<f64>) -> Option<Self> {
+        let rated_crit = rated_max
+            .filter(|_| query.crit.is_none())
+            .map(|max| max - query.rated_margin);
+        let crit_c = query.crit.or(rated_crit);
+        (query.warn.is_some() || crit_c.is_some()).then_some(Thresholds {
+            warn_c: query.warn,
+            crit_c,
+            crit_from_rated: rated_crit.is_some(),
+        })
+    }
+
+    fn evaluate(&self, temperature: Option<f64>) -> ThresholdState {
+        match temperature {
+            None => ThresholdState::Unknown,
+            Some(t) if self.crit_c.is_some_and(|crit| t >= crit) => ThresholdState::Crit,
+            Some(t) if self.warn_c.is_some_and(|warn| t >= warn) => ThresholdState::Warn,
+            Some(_) => ThresholdState::Ok,
+        }
+    }
+}
+
+// 温度读数的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum TempSource {
+    // smartctl 的 JSON 字段
+    Json,
+    // JSON 解析失败后从文本中模糊提取，可信度较低
+    Text,
+    // 内核 sysfs/hwmon
+    Sysfs,
+    // ndctl 报告的持久内存健康信息（--pmem）
+    Ndctl,
+    // nvme-cli 的 smart-log（--backend nvme-cli/auto）
+    #[serde(rename = "nvme-cli")]
+    NvmeCli,
+}
+
+// 单次查询得到的读数
+#[derive(Debug, Clone)]
+struct DiskReading {
+    vendor: Option<String>,
+    model: Option<String>,
+    // 摄氏度；hwmon 等来源以及多次采样的均值可能带小数
+    temperature: Option<f64>,
+    temperature_source: Option<TempSource>,
+    // --repeat 下每次成功采样的温度
+    samples: Vec<f64>,
+    // smartctl 的完整 JSON 输出，供自定义列等功能使用
+    raw: Option<Value>,
+    // 固件以错误单位报告温度时所做的换算
+    unit_correction: Option<UnitCorrection>,
+    // 温度来自 hwmon 时对应的传感器
+    hwmon_source: Option<hwmon::HwmonSource>,
+}
+
+// 对明显不是摄氏度的读数所做的单位换算
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum UnitCorrection {
+    // 固件报告的是 0.1°C
+    Tenths,
+    // 固件报告的是华氏度
+    Fahrenheit,
+    // 以下两种只由 --assume-unit 指定，不参与自动判断
+    Kelvin,
+    Decikelvin,
+}
+
+impl UnitCorrection {
+    // 把按该单位报告的原始值换算为摄氏度，保留一位小数
+    fn to_celsius(self, raw: f64) -> f64 {
+        let celsius = match self {
+            UnitCorrection::Tenths => raw / 10.0,
+            UnitCorrection::Fahrenheit => (raw - 32.0) * 5.0 / 9.0,
+            UnitCorrection::Kelvin => raw - 273.15,
+            UnitCorrection::Decikelvin => raw / 10.0 - 273.15,
+        };
+        (celsius * 10.0).round() / 10.0
+    }
+
+    // --assume-unit 的单位对应的换算，摄氏度不需要换算
+    fn for_assumed(unit: cli::TempUnit) -> Option<Self> {
+        match unit {
+            cli::TempUnit::Celsius => None,
+            cli::TempUnit::Decicelsius => Some(UnitCorrection::Tenths),
+            cli::TempUnit::Fahrenheit => Some(UnitCorrection::Fahrenheit),
+            cli::TempUnit::Kelvin => Some(UnitCorrection::Kelvin),
+            cli::TempUnit::Decikelvin => Some(UnitCorrection::Decikelvin),
+        }
+    }
+}
+
+// 超过该值的"摄氏度"读数视为单位错误
+const IMPLAUSIBLE_CELSIUS: f64 = 120.0;
+// 换算后的读数必须落在正常工作温度范围内才会被采用
+const CORRECTED_PLAUSIBLE: std::ops::RangeInclusive<f64> = 15.0..=80.0;
+
+// 读数高得不合理时，尝试按 0.1°C 或华氏度换算；只有恰好一种换算结果合理时才采用，
+// 两种都合理（无法判断）或都不合理时保留原值
+fn correct_temperature_unit(temperature: f64) -> Option<(f64, UnitCorrection)> {
+    if temperature <= IMPLAUSIBLE_CELSIUS {
+        return None;
+    }
+    let candidates: Vec<(f64, UnitCorrection)> =
+        [UnitCorrection::Tenths, UnitCorrection::Fahrenheit]
+            .into_iter()
+            .map(|correction| (correction.to_celsius(temperature), correction))
+            .filter(|(t, _)| CORRECTED_PLAUSIBLE.contains(t))
+            .collect();
+    match candidates[..] {
+        [candidate] => Some(candidate),
+        _ => None,
+    }
+}
+
+impl DiskReading {
+    // 对单位错误的读数做换算并记录下来
+    fn with_unit_correction(mut self) -> Self {
+        if let Some((t, correction)) = self.temperature.and_then(correct_temperature_unit) {
+            self.temperature = Some(t);
+            self.unit_correction = Some(correction);
+        }
+        self
+    }
+
+    // --assume-unit：按用户指定的单位换算，不做自动判断
+    fn with_assumed_unit(mut self, unit: cli::TempUnit) -> Self {
+        self.unit_correction = UnitCorrection::for_assumed(unit);
+        if let (Some(t), Some(correction)) = (self.temperature, self.unit_correction) {
+            self.temperature = Some(correction.to_celsius(t));
+        }
+        self
+    }
+
+    // --max-field-len：截断过长的厂商、型号和序列号，序列号直接改写在原始 JSON 中
+    fn with_field_limit(mut self, max_len: usize, device: &str) -> Self {
+        for (name, value) in [("vendor", &mut self.vendor), ("model", &mut self.model)] {
+            if let Some(value) = value {
+                truncate_field(value, max_len, device, name);
+            }
+        }
+        if let Some(Value::String(serial)) = self
+            .raw
+            .as_mut()
+            .and_then(|raw| raw.get_mut("serial_number"))
+        {
+            truncate_field(serial, max_len, device, "serial");
+        }
+        self
+    }
+
+    // --power-mode 下 smartctl 因设备处于低功耗状态而跳过了查询
+    fn skipped_for_power_mode(&self) -> bool {
+        self.temperature.is_none() && self.raw.as_ref().is_some_and(reports_low_power)
+    }
+
+    // smartctl JSON 中的版本号，如 [7, 3] 转换为 "7.3"
+    fn smartctl_version(&self) -> Option<String> {
+        let parts = self.raw.as_ref()?["smartctl"]["version"].as_array()?;
+        let version: Vec<String> = parts.iter().map(|p| p.to_string()).collect();
+        (!version.is_empty()).then(|| version.join("."))
+    }
+
+    fn drive_trip(&self) -> Option<i64> {
+        self.raw.as_ref()?["temperature"]["drive_trip"].as_i64()
+    }
+
+    fn lifetime_max(&self) -> Option<i64> {
+        let raw = self.raw.as_ref()?;
+        scsi_environment_temperature(raw, "lifetime_maximum")
+            .or_else(|| raw["temperature"]["lifetime_max"].as_i64())
+    }
+
+    // 用户可用容量（字节）；NVMe 没有 user_capacity 时用控制器的总容量
+    fn capacity(&self) -> Option<u64> {
+        let raw = self.raw.as_ref()?;
+        raw["user_capacity"]["bytes"]
+            .as_u64()
+            .or_else(|| raw["nvme_total_capacity"].as_u64())
+            .filter(|&bytes| bytes > 0)
+    }
+
+    fn model_family(&self) -> Option<&str> {
+        self.raw.as_ref()?["model_family"].as_str()
+    }
+
+    fn serial(&self) -> Option<String> {
+        let serial = self.raw.as_ref()?["serial_number"].as_str()?.trim();
+        (!serial.is_empty()).then(|| serial.to_string())
+    }
+
+    // ATA 属性的温度原始值；raw.value 的高位字节常带有最低/最高温度，以 raw.string 的首个数字为准
+    fn ata_attribute_temperature(&self, id: u32) -> Option<i64> {
+        let attr = self.raw.as_ref()?["ata_smart_attributes"]["table"]
+            .as_array()?
+            .iter()
+            .find(|attr| attr["id"].as_u64() == Some(u64::from(id)))?;
+        attr["raw"]["string"]
+            .as_str()
+            .and_then(|s| s.split_whitespace().next()?.parse().ok())
+            .or_else(|| attr["raw"]["value"].as_i64().map(|v| v & 0xff))
+    }
+
+    // 所有温度属性的读数，供 --all-temps 显示
+    fn ata_attribute_temperatures(&self) -> BTreeMap<u32, i64> {
+        ATA_TEMPERATURE_ATTRIBUTES
+            .iter()
+            .filter_map(|&id| Some((id, self.ata_attribute_temperature(id)?)))
+            .collect()
+    }
+
+    // WHEN_FAILED 非空的 ATA 属性，如 "5 Reallocated_Sector_Ct (now)"；
+    // smartctl 的 JSON 中为 "now"/"past"，对应文本输出的 FAILING_NOW/In_the_past
+    fn failing_attributes(&self) -> Vec<String> {
+        let Some(table) = self
+            .raw
+            .as_ref()
+            .and_then(|raw| raw["ata_smart_attributes"]["table"].as_array())
+        else {
+            return Vec::new();
+        };
+        table
+            .iter()
+            .filter_map(|attr| {
+                let when = attr["when_failed"]
+                    .as_str()
+                    .filter(|w| !w.trim().is_empty())?;
+                Some(format!(
+                    "{} {} ({when})",
+                    attr["id"].as_u64()?,
+                    attr["name"].as_str().unwrap_or("Unknown_Attribute")
+                ))
+            })
+            .collect()
+    }
+
+    // NVMe 健康日志 critical_warning 中置位的各项，如 ["temp", "reliability"]
+    fn nvme_critical_warnings(&self) -> Vec<String> {
+        let Some(bits) = self
+            .raw
+            .as_ref()
+            .and_then(|raw| raw["nvme_smart_health_information_log"]["critical_warning"].as_u64())
+        else {
+            return Vec::new();
+        };
+        NVME_CRITICAL_WARNINGS
+            .iter()
+            .enumerate()
+            .filter(|&(bit, _)| bits & (1 << bit) != 0)
+            .map(|(_, name)| name.to_string())
+            .collect()
+    }
+
+    // NVMe 健康日志 temperature_sensors 中各传感器的读数；未实现的传感器为 null，不占用编号
+    fn nvme_sensor_temperatures(&self) -> BTreeMap<u32, i64> {
+        let Some(sensors) = self.raw.as_ref().and_then(|raw| {
+            raw["nvme_smart_health_information_log"]["temperature_sensors"].as_array()
+        }) else {
+            return BTreeMap::new();
+        };
+        (1..)
+            .zip(sensors)
+            .filter_map(|(n, t)| Some((n, t.as_i64()?)))
+            .collect()
+    }
+
+    // NVMe 由协议判断，其余按 rotation_rate 区分（0 表示 SSD），缺失时无法判断
+    fn drive_type(&self) -> Option<DriveType> {
+        let raw = self.raw.as_ref()?;
+        if raw["device"]["protocol"].as_str() == Some("NVMe") {
+            return Some(DriveType::Nvme);
+        }
+        match raw["rotation_rate"].as_u64()? {
+            0 => Some(DriveType::Ssd),
+            _ => Some(DriveType::Hdd),
+        }
+    }
+
+    // ATA 的 wwn 由 NAA(4 位)、OUI(24 位)、ID(36 位) 组成；SCSI 直接给出 logical_unit_id
+    fn wwn(&self) -> Option<String> {
+        let raw = self.raw.as_ref()?;
+        let wwn = &raw["wwn"];
+        match (wwn["naa"].as_u64(), wwn["oui"].as_u64(), wwn["id"].as_u64()) {
+            (Some(naa), Some(oui), Some(id)) => Some(format!("0x{naa:x}{oui:06x}{id:09x}")),
+            _ => raw["logical_unit_id"].as_str().map(str::to_lowercase),
+        }
+    }
+}
+
+// 报告温度的 ATA 属性：194 Temperature_Celsius 与 190 Airflow_Temperature_Cel（部分 WD 盘两者不同）
+const ATA_TEMPERATURE_ATTRIBUTES: [u32; 2] = [194, 190];
+
+// NVMe critical_warning 各位（第 0 位起）的含义：可用备用空间低于阈值、温度越过阈值、
+// 可靠性下降、介质只读、易失性存储备份失效、持久内存区只读
+const NVME_CRITICAL_WARNINGS: [&str; 6] =
+    ["spare", "temp", "reliability", "read-only", "backup", "pmr"];
+
+// 两次采样之间的间隔
+const REPEAT_INTERVAL: Duration = Duration::from_millis(500);
+
+// 解析 smartctl 的标准输出，与进程执行分开以便用抓取的输出测试；
+// temp_attr 为 --temp-attr 指定的 ATA 属性号，只影响文本回退时的选择
+fn parse_smartctl_output(stdout: &[u8], temp_attr: Option<u32>) -> io::Result<DiskReading> {
+    let output_str = String::from_utf8_lossy(stdout);
 
     // 尝试解析 JSON 格式的输出
     let json_data: Value = match from_str(&output_str) {
         Ok(data) => data,
         Err(e) => {
             // 如果 JSON 解析失败，尝试从原始输出中提取信息
-            if let Some(temp) = extract_temperature_from_text(&output_str) {
-                return Ok((
-                    "Unknown Vendor".to_string(),
-                    "Unknown Model".to_string(),
-                    Some(temp),
-                ));
+            if let Some(temp) = extract_temperature_from_text(&output_str, temp_attr) {
+                return Ok(DiskReading {
+                    vendor: None,
+                    model: None,
+                    temperature: Some(temp as f64),
+                    temperature_source: Some(TempSource::Text),
+                    samples: Vec::new(),
+                    raw: None,
+                    unit_correction: None,
+                    hwmon_source: None,
+                });
             }
             return Err(io::Error::new(
                 io::ErrorKind::InvalidData,
@@ -31,21 +537,20 @@ fn parse_smartctl_output(output: &Output) -> io::Result<(String, String, Option<
             ));
         }
     };
+    Ok(parse_smartctl_json(json_data))
+}
 
+// 从 smartctl 的 JSON 输出中提取厂商、型号和温度
+fn parse_smartctl_json(json_data: Value) -> DiskReading {
     // 提取厂商名 - 优先从model_family中提取（适用于SATA硬盘）
     let vendor = if let Some(model_family) = json_data["model_family"].as_str() {
         // 尝试从model_family中提取厂商名（通常是第一个单词）
-        model_family
-            .split_whitespace()
-            .next()
-            .unwrap_or("Unknown Vendor")
-            .to_string()
+        model_family.split_whitespace().next().map(str::to_string)
     } else {
         json_data["vendor"]
             .as_str()
             .or_else(|| json_data["scsi_vendor"].as_str())
-            .unwrap_or("Unknown Vendor")
-            .to_string()
+            .map(str::to_string)
     };
 
     // 提取模型名
@@ -54,8 +559,7 @@ fn parse_smartctl_output(output: &Output) -> io::Result<(String, String, Option<
         .or_else(|| json_data["product"].as_str()) // 对于SATA设备可能使用product字段
         .or_else(|| json_data["scsi_product"].as_str())
         .or_else(|| json_data["scsi_model_name"].as_str())
-        .unwrap_or("Unknown Model")
-        .to_string();
+        .map(str::to_string);
 
     // 提取温度信息（按优先顺序查询可能的字段）
     let temperature = json_data["temperature"]["current"]
@@ -81,19 +585,184 @@ fn parse_smartctl_output(output: &Output) -> io::Result<(String, String, Option<
                         .next()
                 })
         })
-        .or_else(|| json_data["sata_temperature"].as_i64()); // 添加SATA特定温度字段
+        .or_else(|| json_data["sata_temperature"].as_i64()) // 添加SATA特定温度字段
+        .or_else(|| scsi_environment_temperature(&json_data, "current"))
+        .or_else(|| ata_devstat_temperature(&json_data))
+        .map(|t| t as f64);
+
+    DiskReading {
+        vendor,
+        model,
+        temperature_source: temperature.map(|_| TempSource::Json),
+        temperature,
+        samples: Vec::new(),
+        raw: Some(json_data),
+        unit_correction: None,
+        hwmon_source: None,
+    }
+}
+
+// SCSI 环境报告日志（smartctl -l envrep）中第一个温度传感器的指定字段
+fn scsi_environment_temperature(json_data: &Value, field: &str) -> Option<i64> {
+    let reports = json_data["scsi_environmental_reports"].as_object()?;
+    reports
+        .iter()
+        .filter(|(name, _)| name.starts_with("temperature"))
+        .find_map(|(_, report)| report[field].as_i64())
+}
+
+// 部分 SATA 盘只在设备统计日志（-l devstat）的 "Current Temperature" 项中报告温度
+fn ata_devstat_temperature(json_data: &Value) -> Option<i64> {
+    json_data["ata_device_statistics"]["pages"]
+        .as_array()?
+        .iter()
+        .filter_map(|page| page["table"].as_array())
+        .flatten()
+        .filter(|entry| entry["flags"]["valid"].as_bool() != Some(false))
+        .find(|entry| {
+            entry["name"]
+                .as_str()
+                .is_some_and(|name| name.eq_ignore_ascii_case("Current Temperature"))
+        })
+        .and_then(|entry| entry["value"].as_i64())
+}
+
+// 额外读取一个 smartctl 日志（-l LOG），把其中的 key 合并到原始 JSON 后重新解析
+fn merge_smartctl_log(
+    device: &str,
+    device_type: Option<&str>,
+    reading: DiskReading,
+    query: &QueryArgs,
+    log: &str,
+    key: &str,
+) -> DiskReading {
+    let Some(mut raw) = reading.raw.clone() else {
+        return reading;
+    };
+    let mut args = vec!["--json", "-l", log];
+    if let Some(t) = device_type {
+        args.extend(["-d", t]);
+    }
+    args.push(device);
+    let Ok(output) = execute_smartctl(&query.smartctl, &args) else {
+        return reading;
+    };
+    let Ok(extra) = from_str::<Value>(&String::from_utf8_lossy(&output.stdout)) else {
+        return reading;
+    };
+    match (raw.as_object_mut(), extra.get(key)) {
+        (Some(object), Some(value)) => {
+            object.insert(key.to_string(), value.clone());
+            parse_smartctl_json(raw)
+        }
+        _ => reading,
+    }
+}
+
+fn protocol(reading: &DiskReading) -> Option<&str> {
+    reading.raw.as_ref()?["device"]["protocol"].as_str()
+}
+
+// 使用指定的 -d 类型（None 表示不指定）执行一次完整查询
+fn query_smartctl(
+    device: &str,
+    device_type: Option<&str>,
+    query: &QueryArgs,
+) -> io::Result<DiskReading> {
+    let mut args = vec!["--json", "-a"];
+    if let Some(t) = device_type {
+        args.extend(["-d", t]);
+    }
+    args.push(device);
+    let output = execute_smartctl(&query.smartctl, &args)?;
+    let mut reading = parse_smartctl_output(&output.stdout, query.temp_attr)?;
+    // SCSI 设备按需额外读取环境报告日志
+    if query.scsi_envrep && protocol(&reading) == Some("SCSI") {
+        reading = merge_smartctl_log(
+            device,
+            device_type,
+            reading,
+            query,
+            "envrep",
+            "scsi_environmental_reports",
+        );
+    }
+    // ATA 设备在 -a 中没有温度时，最后再查一次设备统计日志
+    if reading.temperature.is_none() && protocol(&reading) == Some("ATA") {
+        reading = merge_smartctl_log(
+            device,
+            device_type,
+            reading,
+            query,
+            "devstat",
+            "ata_device_statistics",
+        );
+    }
+    // --temp-attr 指定的属性存在时优先于 smartctl 选出的温度
+    if let Some(temp) = query
+        .temp_attr
+        .and_then(|id| reading.ata_attribute_temperature(id))
+    {
+        reading.temperature = Some(temp as f64);
+    }
+    Ok(finish_reading(reading, device, query))
+}
+
+// JSON 与文本模式共用的收尾：截断过长的字段，再处理单位——--assume-unit 指定的设备按指定单位换算，
+// 其余自动判断
+fn finish_reading(reading: DiskReading, device: &str, query: &QueryArgs) -> DiskReading {
+    let assumed = query
+        .assume_unit
+        .iter()
+        .find(|(d, _)| d == device)
+        .map(|&(_, unit)| unit);
+    let reading = reading.with_field_limit(query.max_field_len as usize, device);
+    match assumed {
+        Some(unit) => reading.with_assumed_unit(unit),
+        None => reading.with_unit_correction(),
+    }
+}
 
-    Ok((vendor, model, temperature))
+// 超过 max_len 个字符的字段截断为 max_len - 1 个字符加省略号，并在 stderr 上说明
+fn truncate_field(value: &mut String, max_len: usize, device: &str, name: &str) {
+    let len = value.chars().count();
+    if len <= max_len {
+        return;
+    }
+    eprintln!("Note: {device} reported a {len}-character {name}; truncated to {max_len}");
+    *value = value.chars().take(max_len - 1).chain(['…']).collect();
 }
 
 // 从文本输出中提取温度（备用方法）
-fn extract_temperature_from_text(output: &str) -> Option<i64> {
+fn extract_temperature_from_text(output: &str, temp_attr: Option<u32>) -> Option<i64> {
+    // 先按 ATA 属性表取原始值：指定的属性，其次 194（Temperature_Celsius），最后 190（Airflow）
+    let attributes: BTreeMap<u32, i64> = output
+        .lines()
+        .filter_map(text_attribute_temperature)
+        .collect();
+    if let Some(&temp) = temp_attr
+        .iter()
+        .chain(&ATA_TEMPERATURE_ATTRIBUTES)
+        .find_map(|id| attributes.get(id))
+    {
+        return Some(temp);
+    }
+
+    // 其次是 SCSI 的 "Current Drive Temperature: 31 C" 和 NVMe 的 "Temperature: 42 Celsius"
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        if matches!(key.trim(), "Current Drive Temperature" | "Temperature") {
+            if let Some(temp) = value.split_whitespace().next().and_then(|t| t.parse().ok()) {
+                return Some(temp);
+            }
+        }
+    }
+
     // 尝试匹配常见的温度格式
     for line in output.lines() {
-        if line.to_lowercase().contains("temperature")
-            || line.to_lowercase().contains("airflow_temperature")
-            || line.to_lowercase().contains("temp")
-        {
+        if line.to_lowercase().contains("temperature") || line.to_lowercase().contains("temp") {
             if let Some(temp) = line
                 .split_whitespace()
                 .filter_map(|word| word.parse::<i64>().ok())
@@ -106,142 +775,1746 @@ fn extract_temperature_from_text(output: &str) -> Option<i64> {
     None
 }
 
-// 获取系统中所有硬盘设备
-fn get_all_disk_devices() -> io::Result<Vec<String>> {
-    let output = Command::new("lsblk")
-        .arg("-d")
-        .arg("-o")
-        .arg("NAME,TYPE")
-        .arg("-n")
-        .arg("-l")
-        .output()?;
+// 解析 smartctl -A 文本中的温度属性行，如
+// "194 Temperature_Celsius 0x0022 036 054 000 Old_age Always - 36 (Min/Max 20/54)"
+fn text_attribute_temperature(line: &str) -> Option<(u32, i64)> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() < 10 || !parts[1].to_lowercase().contains("temp") {
+        return None;
+    }
+    Some((parts[0].parse().ok()?, parts[9].parse().ok()?))
+}
 
-    if !output.status.success() {
-        return Err(io::Error::new(
-            io::ErrorKind::Other,
-            format!(
+// lsblk 中属于组合/虚拟设备的 TYPE：md 阵列和 device-mapper（LVM、加密卷、多路径）
+const VIRTUAL_DEVICE_TYPES: [&str; 12] = [
+    "md", "linear", "raid0", "raid1", "raid4", "raid5", "raid6", "raid10", "lvm", "crypt", "dm",
+    "mpath",
+];
+
+// lsblk 报告为 disk、但没有 SMART 的块设备名前缀：zram 压缩内存盘、ram 盘、loop、光驱、ZFS zvol、
+// 软驱、网络块设备，以及 virtio-blk 和 Xen 的虚拟盘
+const VIRTUAL_NAME_PREFIXES: [&str; 9] =
+    ["zram", "ram", "loop", "sr", "zd", "fd", "nbd", "vd", "xvd"];
+
+// 虚拟机模拟的 SCSI/ATA 盘在 sysfs device/model 中的型号前缀（小写）：QEMU、VirtualBox、
+// VMware（"Virtual disk"）和 Hyper-V（"Virtual Disk"）
+const VIRTUAL_DISK_MODELS: [&str; 3] = ["qemu harddisk", "vbox harddisk", "virtual disk"];
+
+// 默认跳过的虚拟设备，--include-virtual 时仍会查询
+fn is_virtual_disk(device: &str) -> bool {
+    let name = device.trim_start_matches("/dev/");
+    VIRTUAL_NAME_PREFIXES
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+        || hwmon::read_attr(&Path::new("/sys/block").join(name).join("device/model")).is_some_and(
+            |model| {
+                let model = model.to_lowercase();
+                VIRTUAL_DISK_MODELS
+                    .iter()
+                    .any(|prefix| model.starts_with(prefix))
+            },
+        )
+}
+
+// 获取要查询的硬盘设备，并按 --devices-glob、--exclude-pattern 过滤
+fn get_all_disk_devices(discovery: &DiscoveryArgs) -> io::Result<Vec<String>> {
+    let mut devices = discover_devices(discovery)?;
+    if !discovery.devices_glob.is_empty() {
+        let patterns = discovery
+            .devices_glob
+            .iter()
+            .map(|p| glob::Pattern::new(p))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                io::Error::new(io::ErrorKind::InvalidInput, format!("--devices-glob: {e}"))
+            })?;
+        devices.retain(|device| patterns.iter().any(|p| p.matches(device)));
+    }
+    if !discovery.exclude_pattern.is_empty() {
+        let patterns = discovery
+            .exclude_pattern
+            .iter()
+            .map(|p| regex::Regex::new(p))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("--exclude-pattern: {e}"),
+                )
+            })?;
+        devices.retain(|device| !patterns.iter().any(|p| p.is_match(device)));
+    }
+    Ok(devices)
+}
+
+// 获取系统中所有硬盘设备（命令行指定了设备时只解析这些设备）
+//
+// 默认只返回 TYPE 为 disk 的物理设备，md/LVM 的成员盘本身就是 disk，因此总会被查询；
+// include_virtual 为真时额外包含 md 阵列和 device-mapper 设备（它们通常不支持 SMART）。
+fn discover_devices(discovery: &DiscoveryArgs) -> io::Result<Vec<String>> {
+    // 命令行指定了设备时只查询这些设备，多个参数指向同一块磁盘时只保留一个
+    if !discovery.devices.is_empty() {
+        let mut devices: Vec<String> = Vec::new();
+        for spec in &discovery.devices {
+            let device = resolve::resolve_device(spec)?;
+            if !devices.contains(&device) && !discovery.exclude.contains(&device) {
+                devices.push(device);
+            }
+        }
+        return Ok(devices);
+    }
+
+    // --list-cmd 完全替代 lsblk，用于没有 lsblk 的平台
+    if let Some(command) = &discovery.list_cmd {
+        return list_devices_with(command, discovery);
+    }
+
+    let entries = match Command::new(&discovery.lsblk_path)
+        .args(["-d", "-o", "NAME,TYPE", "-n", "-l"])
+        .output()
+    {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                Some((parts.next()?.to_string(), parts.next()?.to_string()))
+            })
+            .collect(),
+        Ok(output) => {
+            return Err(io::Error::other(format!(
                 "lsblk command failed: {}",
                 String::from_utf8_lossy(&output.stderr)
-            ),
-        ));
-    }
+            )))
+        }
+        // 精简系统上可能没有 lsblk，退回到直接枚举 /sys/block
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            eprintln!(
+                "{} not found; listing drives from /sys/block instead",
+                discovery.lsblk_path.display()
+            );
+            sysfs_block_devices()?
+        }
+        Err(e) => {
+            return Err(io::Error::new(
+                e.kind(),
+                format!("Failed to run {}: {e}", discovery.lsblk_path.display()),
+            ))
+        }
+    };
 
     // 解析输出获取设备列表
-    let devices = String::from_utf8_lossy(&output.stdout)
-        .lines()
-        .filter_map(|line| {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 2 && parts[1] == "disk" {
-                let device_path = format!("/dev/{}", parts[0]);
-                if !device_path.starts_with("/dev/zd") && !device_path.starts_with("/dev/fd") {
-                    return Some(device_path);
-                }
-            }
-            None
+    let devices = entries
+        .into_iter()
+        .filter_map(|(name, kind)| {
+            let wanted = kind == "disk"
+                || (discovery.include_virtual && VIRTUAL_DEVICE_TYPES.contains(&kind.as_str()));
+            let device_path = format!("/dev/{name}");
+            (wanted
+                && (discovery.include_virtual || !is_virtual_disk(&device_path))
+                && !mmc::is_hardware_partition(&device_path)
+                && !discovery.exclude.contains(&device_path))
+            .then_some(device_path)
         })
-        .collect();
+        .collect::<Vec<String>>();
+
+    Ok(devices)
+}
+
+// 没有 lsblk 时从 /sys/block 枚举整盘，并按 lsblk 的规则推断 TYPE：
+// md 阵列记为 md，device-mapper 记为 dm，loop、ramdisk 和光驱不是 disk，其余视为 disk
+fn sysfs_block_devices() -> io::Result<Vec<(String, String)>> {
+    let mut entries = Vec::new();
+    for entry in std::fs::read_dir("/sys/block")? {
+        let name = entry?.file_name().to_string_lossy().into_owned();
+        let kind = if name.starts_with("md") {
+            "md"
+        } else if name.starts_with("dm-") {
+            "dm"
+        } else if name.starts_with("loop") || name.starts_with("ram") {
+            "loop"
+        } else if name.starts_with("sr") {
+            "rom"
+        } else {
+            "disk"
+        };
+        entries.push((name, kind.to_string()));
+    }
+    entries.sort();
+    Ok(entries)
+}
 
+// 运行 --list-cmd，标准输出的每个非空行为一个设备
+fn list_devices_with(command: &str, discovery: &DiscoveryArgs) -> io::Result<Vec<String>> {
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .output()
+        .map_err(|e| io::Error::new(e.kind(), format!("Failed to run --list-cmd: {e}")))?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "--list-cmd exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    let mut devices: Vec<String> = Vec::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let device = line.trim().to_string();
+        if !device.is_empty() && !devices.contains(&device) && !discovery.exclude.contains(&device)
+        {
+            devices.push(device);
+        }
+    }
     Ok(devices)
 }
 
+// 依次尝试的 -d 类型（空字符串表示不指定）；--types 给出时严格按其顺序，其中 "default" 表示不指定。
+// 否则按命名惯例识别的 NVMe 设备直接从 -d nvme 开始，省去一次缓慢且无用的默认尝试
+fn device_types_for<'a>(device: &str, smartctl: &'a SmartctlArgs) -> Vec<&'a str> {
+    if !smartctl.types.is_empty() {
+        return smartctl
+            .types
+            .iter()
+            .map(|t| if t == "default" { "" } else { t.as_str() })
+            .collect();
+    }
+    let mut device_types = DEVICE_TYPES.to_vec();
+    if device.trim_start_matches("/dev/").starts_with("nvme") {
+        device_types.retain(|&t| t != "nvme");
+        device_types.insert(0, "nvme");
+    }
+    device_types
+}
+
+// list --json 的单个设备：只有身份信息，不含温度
+#[derive(Debug, Serialize)]
+struct InventoryEntry {
+    device: String,
+    vendor: Option<String>,
+    model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    serial: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    wwn: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    drive_type: Option<DriveType>,
+}
+
+// list --json 的顶层结构
+#[derive(Debug, Serialize)]
+struct Inventory {
+    version: u32,
+    devices: Vec<InventoryEntry>,
+}
+
+// 用 smartctl -i 读取设备身份信息，比完整查询快，也不会读取 SMART 日志
+fn identify_device(device: &str, smartctl: &SmartctlArgs) -> InventoryEntry {
+    let reading = if mmc::is_mmc_device(device) {
+        mmc::get_disk_info_and_temperature(device).ok()
+    } else {
+        let mut first = None;
+        let mut found = None;
+        for device_type in device_types_for(device, smartctl) {
+            let mut args = vec!["--json", "-i"];
+            if !device_type.is_empty() {
+                args.extend(["-d", device_type]);
+            }
+            args.push(device);
+            let Ok(output) = execute_smartctl(smartctl, &args) else {
+                continue;
+            };
+            let Ok(json) = from_str::<Value>(&String::from_utf8_lossy(&output.stdout)) else {
+                continue;
+            };
+            let reading = parse_smartctl_json(json);
+            if reading.model.is_some() {
+                found = Some(reading);
+                break;
+            }
+            first.get_or_insert(reading);
+        }
+        found.or(first)
+    };
+
+    match reading {
+        Some(reading) => InventoryEntry {
+            device: device.to_string(),
+            serial: reading.serial(),
+            wwn: reading.wwn(),
+            drive_type: reading.drive_type(),
+            vendor: reading.vendor,
+            model: reading.model,
+        },
+        None => InventoryEntry {
+            device: device.to_string(),
+            vendor: None,
+            model: None,
+            serial: None,
+            wwn: None,
+            drive_type: None,
+        },
+    }
+}
+
 // 尝试为每个设备调用 smartctl 并自动切换 -d 参数
-fn get_disk_info_and_temperature(device: &str) -> io::Result<(String, String, Option<i64>)> {
-    // 首先尝试不带任何设备类型参数（适用于大多数SATA设备）
-    let mut args = vec!["--json", "-a", device];
-    let output = execute_smartctl(&args);
-    if let Ok(info) = parse_smartctl_output(&output) {
-        return Ok(info);
+// hint 为 --scan-cache 中记录的设备类型，会最先尝试
+fn get_disk_info_and_temperature(
+    device: &str,
+    query: &QueryArgs,
+    hint: Option<&str>,
+) -> io::Result<DiskReading> {
+    // MMC/SD 设备不支持 smartctl，直接读取 sysfs
+    if mmc::is_mmc_device(device) {
+        return mmc::get_disk_info_and_temperature(device);
+    }
+    // --backend drivetemp 只读内核 hwmon；auto 在没有 drivetemp 传感器时才调用 smartctl；
+    // nvme-cli 只用于 NVMe 设备，其余设备仍用 smartctl
+    let nvme_timeout = Duration::from_secs(query.smartctl.timeout);
+    match query.backend {
+        Backend::Drivetemp => return drivetemp::get_disk_info_and_temperature(device),
+        Backend::Text => return smartctl_text::get_disk_info_and_temperature(device, query),
+        Backend::NvmeCli if nvmecli::is_nvme_device(device) => {
+            return nvmecli::get_disk_info_and_temperature(device, nvme_timeout)
+        }
+        Backend::Auto => {
+            if let Ok(reading) = drivetemp::get_disk_info_and_temperature(device) {
+                return Ok(reading);
+            }
+        }
+        Backend::Smartctl | Backend::NvmeCli => {}
     }
 
-    // 如果默认方式失败，尝试所有设备类型
-    for device_type in DEVICE_TYPES.iter().filter(|&&t| !t.is_empty()) {
-        args = vec!["--json", "-a", "-d", device_type, device];
-        let output = execute_smartctl(&args);
-        if let Ok(info) = parse_smartctl_output(&output) {
-            return Ok(info);
+    // 依次尝试不带设备类型参数（适用于大多数SATA设备）和所有 -d 类型；
+    // 查询成功但没有温度时（如 USB 桥接只返回部分数据）继续尝试，都没有温度时返回第一个成功的读数
+    let mut partial = None;
+    let mut device_types = device_types_for(device, &query.smartctl);
+    if let Some(hint) = hint {
+        device_types.retain(|&t| t != hint);
+        device_types.insert(0, hint);
+    }
+    // 不带 -d 的默认尝试是否已经失败（出错或没有温度）
+    let mut default_failed = false;
+    for device_type in device_types {
+        let device_type = Some(device_type).filter(|t| !t.is_empty());
+        match query_smartctl(device, device_type, query) {
+            Ok(info) if info.temperature.is_some() => {
+                // 只在默认尝试失败后由循环选中的类型上交叉验证；用户用 --types 限定了尝试范围、
+                // 类型来自缓存（建缓存时已验证过），或类型本身就是 auto 会选的（auto、nvme）时不做
+                if let Some(t) = device_type.filter(|&t| {
+                    default_failed
+                        && !["auto", "nvme"].contains(&t)
+                        && query.smartctl.types.is_empty()
+                        && Some(t) != hint
+                }) {
+                    cross_check_auto(device, t, &info, query);
+                }
+                return Ok(info);
+            }
+            // 设备处于低功耗状态时换其他 -d 类型也一样会被跳过
+            Ok(info) if info.skipped_for_power_mode() => return Ok(info),
+            Ok(info) => {
+                default_failed |= device_type.is_none();
+                partial.get_or_insert(info);
+            }
+            // 重试后仍超时的设备换其他 -d 类型也不会响应，不再逐个等待
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => return Err(e),
+            Err(_) => default_failed |= device_type.is_none(),
         }
     }
 
-    Err(io::Error::new(
-        io::ErrorKind::Other,
-        format!("Failed for device: {}", device),
-    ))
+    // NVMe-oF 等场景下命名空间本身可能读不到健康日志，再试一次所属控制器的字符设备
+    if let Some(controller) = resolve::nvme_controller(device) {
+        let path = format!("/dev/{controller}");
+        if let Ok(reading) = query_smartctl(&path, Some("nvme"), query) {
+            if reading.temperature.is_some() {
+                return Ok(reading);
+            }
+        }
+    }
+
+    // --backend auto 下 smartctl 仍读不到温度的 NVMe 设备，最后交给 nvme-cli
+    if query.backend == Backend::Auto && nvmecli::is_nvme_device(device) {
+        if let Ok(reading) = nvmecli::get_disk_info_and_temperature(device, nvme_timeout) {
+            if reading.temperature.is_some() {
+                return Ok(reading);
+            }
+        }
+    }
+
+    partial.ok_or_else(|| io::Error::other(format!("Failed for device: {}", device)))
+}
+
+// 不带 -d 的默认尝试失败后由循环选中某个 -d 类型时，再用 -d auto 查询一次并比较温度；
+// 对某些 USB 桥接，错误的类型也能返回看似合理但不正确的读数
+fn cross_check_auto(device: &str, device_type: &str, reading: &DiskReading, query: &QueryArgs) {
+    let Ok(auto) = query_smartctl(device, Some("auto"), query) else {
+        return;
+    };
+    if let (Some(chosen), Some(detected)) = (reading.temperature, auto.temperature) {
+        if chosen != detected {
+            eprintln!(
+                "Warning: {device} reports {chosen}°C with -d {device_type} but {detected}°C with -d auto"
+            );
+        }
+    }
+}
+
+// 对设备采样多次并聚合温度，部分采样失败时只使用成功的读数
+fn sample_disk(device: &str, query: &QueryArgs, hint: Option<&str>) -> io::Result<DiskReading> {
+    let repeat = query.repeat;
+    let mut last_reading = None;
+    let mut last_error = None;
+    let mut temps = Vec::with_capacity(repeat as usize);
+    let mut from_text = false;
+
+    for i in 0..repeat {
+        if i > 0 {
+            thread::sleep(REPEAT_INTERVAL);
+        }
+        match get_disk_info_and_temperature(device, query, hint) {
+            Ok(reading) => {
+                temps.extend(reading.temperature);
+                from_text |= reading.temperature_source == Some(TempSource::Text);
+                last_reading = Some(reading);
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+
+    let Some(mut reading) = last_reading else {
+        return Err(last_error
+            .unwrap_or_else(|| io::Error::other(format!("Failed for device: {}", device))));
+    };
+
+    reading.temperature = match query.repeat_agg {
+        _ if temps.is_empty() => None,
+        RepeatAgg::Max => temps.iter().copied().reduce(f64::max),
+        RepeatAgg::Min => temps.iter().copied().reduce(f64::min),
+        RepeatAgg::Mean => Some(temps.iter().sum::<f64>() / temps.len() as f64),
+    };
+    // 任一采样来自文本提取，聚合结果就不能视为可靠的 JSON 读数
+    if from_text {
+        reading.temperature_source = Some(TempSource::Text);
+    }
+    reading.samples = temps;
+
+    Ok(reading)
+}
+
+// 按 --smartctl-path/--exec-wrapper/--power-mode 构造一次 smartctl 调用
+fn smartctl_command(smartctl: &SmartctlArgs, args: &[&str]) -> Command {
+    // --exec-wrapper 取代 smartctl 本身，其中可以带固定参数（如 "sudo -n /usr/sbin/smartctl"）。
+    // 与 --list-cmd 一样交给 sh 解析，路径中的空格可以用引号括起来；exec 让超时时终止的是
+    // wrapper 本身，smartctl 的参数经 "$@" 原样传入
+    let mut command = match smartctl.exec_wrapper.as_deref() {
+        Some(wrapper) => {
+            let mut command = Command::new("sh");
+            command
+                .arg("-c")
+                .arg(format!("exec {wrapper} \"$@\""))
+                .arg("smartctl");
+            command
+        }
+        None => Command::new(&smartctl.smartctl_path),
+    };
+    // --power-mode 交给 smartctl 自己判断是否唤醒设备；调用方自带 -n 时不再重复
+    if let Some(mode) = smartctl.power_mode.filter(|_| !args.contains(&"-n")) {
+        command.args(["-n", mode.as_arg()]);
+    }
+    command.args(args);
+    command
+}
+
+// 支持 --json 的最低 smartmontools 版本
+const MIN_JSON_SMARTCTL: (u32, u32) = (7, 0);
+
+// smartctl --version 的首行形如 "smartctl 6.2 2013-07-26 r3841 [...]"，取出主、次版本号
+fn parse_smartctl_version(text: &str) -> Option<(u32, u32)> {
+    let version = text
+        .lines()
+        .next()?
+        .strip_prefix("smartctl ")?
+        .split_whitespace()
+        .next()?;
+    let (major, minor) = version.split_once('.')?;
+    let minor: String = minor.chars().take_while(char::is_ascii_digit).collect();
+    Some((major.parse().ok()?, minor.parse().ok()?))
+}
+
+// 启动时确认 smartctl 支持 --json（7.0 起），否则每个设备都会以费解的解析错误失败；
+// 无法运行 smartctl 或认不出版本时不做判断，交给实际查询去报告
+fn check_smartctl_version(smartctl: &SmartctlArgs) -> io::Result<()> {
+    let command = smartctl_command(smartctl, &["--version"]);
+    let Ok(Some(output)) = output_within(command, Duration::from_secs(smartctl.timeout)) else {
+        return Ok(());
+    };
+    match parse_smartctl_version(&String::from_utf8_lossy(&output.stdout)) {
+        Some((major, minor)) if (major, minor) < MIN_JSON_SMARTCTL => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "smartmontools >= {}.{} is required for JSON output, found {major}.{minor}",
+                MIN_JSON_SMARTCTL.0, MIN_JSON_SMARTCTL.1
+            ),
+        )),
+        _ => Ok(()),
+    }
+}
+
+// 在后台线程读完管道，避免输出写满管道时子进程阻塞
+fn read_in_background(mut pipe: impl Read + Send + 'static) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = pipe.read_to_end(&mut buf);
+        buf
+    })
+}
+
+// 运行命令并收集输出；超过 timeout 仍未退出时杀掉进程，返回 None
+fn output_within(mut command: Command, timeout: Duration) -> io::Result<Option<Output>> {
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stdout = child.stdout.take().map(read_in_background);
+    let stderr = child.stderr.take().map(read_in_background);
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+    let collect = |reader: Option<thread::JoinHandle<Vec<u8>>>| {
+        reader.and_then(|r| r.join().ok()).unwrap_or_default()
+    };
+    Ok(Some(Output {
+        status,
+        stdout: collect(stdout),
+        stderr: collect(stderr),
+    }))
 }
 
 // 执行smartctl命令的辅助函数
-fn execute_smartctl(args: &[&str]) -> Output {
-    Command::new("smartctl")
-        .args(args)
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::piped())
-        .spawn()
-        .and_then(|child| child.wait_with_output())
-        .unwrap_or_else(|e| {
-            eprintln!("Failed to execute smartctl with args {:?}: {}", args, e);
-            Output {
-                status: std::process::ExitStatus::default(),
-                stdout: Vec::new(),
-                stderr: Vec::new(),
+//
+// 先按 --timeout 等待，超时后用更长的 --retry-timeout 重试一次：大多数设备很快返回，
+// 大容量 SMR 盘等偶尔响应很慢的设备也有机会读到。两次都超时时返回 TimedOut 错误；
+// 无法启动 smartctl 时只打印错误并返回空输出，由调用方按查询失败处理
+fn execute_smartctl(smartctl: &SmartctlArgs, args: &[&str]) -> io::Result<Output> {
+    let attempts = [smartctl.timeout, smartctl.retry_timeout];
+    for (i, &secs) in attempts.iter().enumerate() {
+        match output_within(smartctl_command(smartctl, args), Duration::from_secs(secs)) {
+            Ok(Some(output)) => return Ok(output),
+            Ok(None) if i == 0 => eprintln!(
+                "smartctl {} timed out after {secs}s; retrying with {}s",
+                args.join(" "),
+                smartctl.retry_timeout
+            ),
+            Ok(None) => {}
+            Err(e) => {
+                eprintln!("Failed to execute smartctl with args {:?}: {}", args, e);
+                return Ok(Output {
+                    status: std::process::ExitStatus::default(),
+                    stdout: Vec::new(),
+                    stderr: Vec::new(),
+                });
             }
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::TimedOut,
+        format!("smartctl timed out after {}s", smartctl.retry_timeout),
+    ))
+}
+
+// 查询前检测设备是否处于待机/睡眠状态（smartctl -n standby 不会唤醒设备）
+fn is_in_standby(device: &str, query: &QueryArgs) -> bool {
+    let Ok(output) = execute_smartctl(&query.smartctl, &["--json", "-n", "standby", "-i", device])
+    else {
+        return false;
+    };
+    let output_str = String::from_utf8_lossy(&output.stdout);
+    from_str::<Value>(&output_str).is_ok_and(|json_data| reports_low_power(&json_data))
+}
+
+// smartctl 因 -n 跳过查询时以状态 2 退出，并在消息中给出当前电源模式
+fn reports_low_power(json_data: &Value) -> bool {
+    json_data["smartctl"]["messages"]
+        .as_array()
+        .is_some_and(|messages| {
+            messages.iter().any(|m| {
+                m["string"].as_str().is_some_and(|text| {
+                    let text = text.to_uppercase();
+                    text.contains("STANDBY") || text.contains("SLEEP") || text.contains("IDLE")
+                })
+            })
         })
 }
 
-// 主函数
-fn main() {
-    // 检查是否有 root 权限
+// 影响单个设备查询结果（读数、状态、阈值、自定义列）的选项，作为 --min-interval 缓存的键；
+// 设备选择、输出格式和告警相关的选项不影响缓存的内容，不在其中。新增这类选项时需要加到这里
+#[derive(Serialize)]
+struct ReadingOptions<'a> {
+    smartctl_path: &'a Path,
+    exec_wrapper: Option<&'a str>,
+    types: &'a [String],
+    power_mode: Option<String>,
+    backend: Option<String>,
+    error_on_wakeup: bool,
+    strict_json: bool,
+    scsi_envrep: bool,
+    sanity: bool,
+    warn_on_missing_temp: bool,
+    temp_attr: Option<u32>,
+    all_temps: bool,
+    all_sensors: bool,
+    max_field_len: u32,
+    repeat: u32,
+    repeat_agg: Option<String>,
+    assume_unit: Vec<(&'a str, Option<String>)>,
+    warn: Option<f64>,
+    crit: Option<f64>,
+    rated_margin: f64,
+    rated_table: Option<&'a Path>,
+    ambient: Option<f64>,
+    config: Option<&'a Path>,
+}
+
+// 枚举按命令行上的取值名记录（如 "nvme-cli"），不依赖 Debug 输出
+fn value_name<T: ValueEnum>(value: &T) -> Option<String> {
+    value.to_possible_value().map(|v| v.get_name().to_string())
+}
+
+impl<'a> ReadingOptions<'a> {
+    fn from_query(query: &'a QueryArgs) -> Self {
+        ReadingOptions {
+            smartctl_path: &query.smartctl.smartctl_path,
+            exec_wrapper: query.smartctl.exec_wrapper.as_deref(),
+            types: &query.smartctl.types,
+            power_mode: query.smartctl.power_mode.as_ref().and_then(value_name),
+            backend: value_name(&query.backend),
+            error_on_wakeup: query.error_on_wakeup,
+            strict_json: query.strict_json,
+            scsi_envrep: query.scsi_envrep,
+            sanity: query.sanity,
+            warn_on_missing_temp: query.warn_on_missing_temp,
+            temp_attr: query.temp_attr,
+            all_temps: query.all_temps,
+            all_sensors: query.all_sensors,
+            max_field_len: query.max_field_len,
+            repeat: query.repeat,
+            repeat_agg: value_name(&query.repeat_agg),
+            assume_unit: query
+                .assume_unit
+                .iter()
+                .map(|(device, unit)| (device.as_str(), value_name(unit)))
+                .collect(),
+            warn: query.warn,
+            crit: query.crit,
+            rated_margin: query.rated_margin,
+            rated_table: query.rated_table.as_deref(),
+            ambient: query.ambient,
+            config: query.config.as_deref(),
+        }
+    }
+}
+
+// 一次完整扫描所需的上下文
+struct Scanner {
+    query: QueryArgs,
+    // 自定义列：列名与编译后的 JSON 路径
+    columns: Vec<(String, config::JsonPath)>,
+    // 并行查询使用的线程池，大小由 --jobs 决定
+    pool: rayon::ThreadPool,
+    // --filter 表达式，只保留满足条件的设备
+    filter: Option<filter::Filter>,
+    // --annotations 映射：设备名、序列号或 WWN -> 备注
+    annotations: BTreeMap<String, String>,
+    // 型号家族的额定最高温度表
+    rated: rated::RatedTable,
+    // 报告中本机的名称（--hostname 或系统主机名）
+    hostname: String,
+}
+
+impl Scanner {
+    // 读取配置文件，并编译自定义列的 JSON 路径
+    fn new(query: &QueryArgs) -> io::Result<Self> {
+        let config = match query.config.as_deref() {
+            Some(path) => config::load(path)?,
+            None => config::Config::default(),
+        };
+        let columns = config
+            .columns
+            .iter()
+            .filter_map(|c| Some((c.name.clone(), config::JsonPath::parse(&c.path).ok()?)))
+            .collect();
+        // num_threads(0) 即 rayon 默认的 CPU 核数
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(query.jobs.map_or(0, |n| n as usize))
+            .build()
+            .map_err(io::Error::other)?;
+        let filter = query
+            .filter
+            .as_deref()
+            .map(filter::Filter::parse)
+            .transpose()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("--filter: {e}")))?;
+        let annotations = match query.annotations.as_deref() {
+            Some(path) => config::load_annotations(path)?,
+            None => BTreeMap::new(),
+        };
+        let rated = rated::RatedTable::load(query.rated_table.as_deref())?;
+        // 读不到系统主机名时与表格 HOST 列一样称本机为 localhost
+        let hostname = query.hostname.clone().unwrap_or_else(|| {
+            nix::unistd::gethostname()
+                .ok()
+                .and_then(|name| name.into_string().ok())
+                .unwrap_or_else(|| "localhost".to_string())
+        });
+        Ok(Scanner {
+            query: query.clone(),
+            columns,
+            pool,
+            filter,
+            annotations,
+            rated,
+            hostname,
+        })
+    }
+
+    fn column_names(&self) -> Vec<&str> {
+        self.columns.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    // 明确指定了 --hostname 或使用了 --remote 时 CSV 加上 host 列；只取决于参数而不是结果，
+    // --log-csv 的表头在各次运行之间保持一致，未使用这两项的已有日志也不受影响
+    fn csv_format(&self) -> CsvFormat<'_> {
+        let tagged = self.query.hostname.is_some() || !self.query.remote.is_empty();
+        CsvFormat {
+            columns: self.column_names(),
+            host: tagged.then(|| self.hostname.clone()),
+        }
+    }
+
+    // 查询本机（除非 --remote-only）和各 --remote 主机的设备，再按 --filter 筛选
+    fn scan(&self) -> io::Result<Vec<DiskInfo>> {
+        let started = state::now_secs();
+        let mut results = if self.query.remote_only {
+            Vec::new()
+        } else {
+            self.scan_local()?
+        };
+        results.extend(self.query_remotes());
+        for info in &mut results {
+            info.taken_at = started.saturating_sub(info.cached_seconds.unwrap_or(0));
+        }
+        if let Some(filter) = &self.filter {
+            results.retain(|info| filter.matches(info));
+        }
+        Ok(results)
+    }
+
+    // --pmem：每个持久内存 DIMM 一行；ndctl 不可用时以一行 FAIL 提示
+    fn query_pmem(&self) -> Vec<DiskInfo> {
+        match pmem::list_dimms() {
+            Ok(dimms) => dimms
+                .into_iter()
+                .map(|(device, reading)| self.disk_info(&device, reading, false))
+                .collect(),
+            Err(e) => vec![self.failed("ndctl", e.to_string())],
+        }
+    }
+
+    // 并行查询各 --remote 主机；主机不可达或输出无法解析时以一行 FAIL 代替其全部设备
+    fn query_remotes(&self) -> Vec<DiskInfo> {
+        self.pool.install(|| {
+            self.query
+                .remote
+                .par_iter()
+                .flat_map(|host| {
+                    remote::query_host(host, &self.query.remote_command).unwrap_or_else(|e| {
+                        vec![DiskInfo {
+                            host: Some(host.clone()),
+                            ..self.failed("-", e.to_string())
+                        }]
+                    })
+                })
+                .collect()
+        })
+    }
+
+    // 获取本机设备列表并并行查询每个设备的厂商名、硬盘型号和温度
+    fn scan_local(&self) -> io::Result<Vec<DiskInfo>> {
+        let mut devices = get_all_disk_devices(&self.query.discovery)?;
+        // --pmem 时持久内存由 ndctl 按 DIMM 报告，其命名空间块设备不再交给 smartctl
+        if self.query.pmem {
+            devices.retain(|device| !pmem::is_pmem_namespace(device));
+        }
+        // 枚举时记下每个设备的 WWID/序列号，设备名在查询途中变化时据此找回
+        let identities: BTreeMap<&str, String> = devices
+            .iter()
+            .filter_map(|device| Some((device.as_str(), resolve::device_identity(device)?)))
+            .collect();
+        let idle = self.idle_devices(&devices);
+        let cache = self.query.scan_cache.as_deref().and_then(|path| {
+            state::ScanCache::load_valid(
+                path,
+                self.query.scan_cache_ttl,
+                &devices,
+                state::now_secs(),
+            )
+        });
+        let hints = cache
+            .as_ref()
+            .map(|c| c.devices.clone())
+            .unwrap_or_default();
+        let mut results = self.query_with_min_interval(&devices, &idle, &hints);
+        if cache.is_none() {
+            self.save_scan_cache(&results);
+        }
+        self.retry_vanished(&mut results, &devices, &identities);
+        warn_inconsistent_paths(&results);
+        if self.query.pmem {
+            results.extend(self.query_pmem());
+        }
+        let memberships = if self.query.pools {
+            pools::memberships()
+        } else {
+            Vec::new()
+        };
+        for info in &mut results {
+            info.bus = resolve::bus_path(&info.device);
+            info.note = self.annotation_for(info);
+            let disk = resolve::disk_of(&info.device);
+            if let Some(member) = memberships.iter().find(|m| m.device == disk) {
+                info.pool = Some(member.pool.clone());
+                info.vdev = member.vdev.clone();
+            }
+            // --label 最后替换设备名，之前按路径进行的查找不受影响
+            if let Some((_, label)) = self.query.label.iter().find(|(d, _)| *d == info.device) {
+                info.device_path = Some(std::mem::replace(&mut info.device, label.clone()));
+            }
+        }
+        Ok(results)
+    }
+
+    // --min-interval：间隔内查询过的设备沿用 readings.json 中的读数，其余设备照常查询后写回
+    fn query_with_min_interval(
+        &self,
+        devices: &[String],
+        idle: &BTreeMap<String, u64>,
+        hints: &BTreeMap<String, Option<String>>,
+    ) -> Vec<DiskInfo> {
+        let min_interval = self.query.min_interval;
+        if min_interval == 0 {
+            return self.query_devices(devices, idle, hints, &BTreeMap::new());
+        }
+        let path = state::ReadingCache::path_for(&self.query.state_file);
+        let mut cache = state::ReadingCache::load(&path);
+        let key = self.reading_cache_key();
+        let fresh = cache.fresh(key, min_interval, state::now_secs());
+        let results = self.query_devices(devices, idle, hints, &fresh);
+        // 失败的设备下次仍然重试，因空闲跳过的设备本来就没有读数
+        let queried: Vec<DiskInfo> = results
+            .iter()
+            .filter(|info| {
+                info.cached_seconds.is_none() && !["FAIL", "SKIP"].contains(&info.status.as_str())
+            })
+            .cloned()
+            .collect();
+        if !queried.is_empty() {
+            cache.record(&queried, key, min_interval, state::now_secs());
+            if let Err(e) = cache.save(&path) {
+                eprintln!("Failed to save reading cache {}: {e}", path.display());
+            }
+        }
+        results
+    }
+
+    // readings.json 中读数的键：ReadingOptions 的 JSON 的 FNV-1a 摘要。换了 --warn、--temp-attr、
+    // --backend 等参数的运行重新查询，不会拿到按旧参数得出的状态；序列化和摘要算法都是固定的，
+    // 升级编译器或依赖后已有的记录仍然有效
+    fn reading_cache_key(&self) -> u64 {
+        let options = ReadingOptions::from_query(&self.query);
+        let json = serde_json::to_vec(&options).unwrap_or_default();
+        state::fnv1a(&json)
+    }
+
+    // 缓存失效（或不存在）时用本次结果重建 --scan-cache
+    fn save_scan_cache(&self, results: &[DiskInfo]) {
+        let Some(path) = &self.query.scan_cache else {
+            return;
+        };
+        let cache = state::ScanCache {
+            created: state::now_secs(),
+            devices: results
+                .iter()
+                .map(|info| {
+                    let hint = info
+                        .smartctl_type
+                        .clone()
+                        .filter(|_| info.temperature.is_some());
+                    (info.device.clone(), hint)
+                })
+                .collect(),
+        };
+        if let Err(e) = cache.save(path) {
+            eprintln!("Failed to save scan cache {}: {e}", path.display());
+        }
+    }
+
+    // 依次按设备名、序列号、WWN 查找备注；序列号和 WWN 不随重启变化
+    fn annotation_for(&self, info: &DiskInfo) -> Option<String> {
+        let name = info.device.trim_start_matches("/dev/");
+        [Some(info.device.as_str()), Some(name)]
+            .into_iter()
+            .chain([info.serial.as_deref(), info.wwn.as_deref()])
+            .flatten()
+            .find_map(|key| self.annotations.get(key))
+            .cloned()
+    }
+
+    // 并行查询所有设备，结果与 devices 顺序一致；cached 中的设备直接使用其中的读数
+    // 同一 NVMe 控制器下的多个命名空间报告的是同一个控制器温度：每个控制器只查询第一个命名空间，
+    // 其余命名空间沿用这次读数，在报告中仍各占一行
+    fn query_devices(
+        &self,
+        devices: &[String],
+        idle: &BTreeMap<String, u64>,
+        hints: &BTreeMap<String, Option<String>>,
+        cached: &BTreeMap<String, DiskInfo>,
+    ) -> Vec<DiskInfo> {
+        let mut queried: Vec<String> = Vec::new();
+        let mut controllers: BTreeMap<String, usize> = BTreeMap::new();
+        let sources: Vec<usize> = devices
+            .iter()
+            .map(|device| {
+                let mut next = || {
+                    queried.push(device.clone());
+                    queried.len() - 1
+                };
+                match resolve::nvme_controller(device) {
+                    Some(controller) => *controllers.entry(controller).or_insert_with(next),
+                    None => next(),
+                }
+            })
+            .collect();
+        let readings = self.query_each(&queried, idle, hints, cached);
+        sources
+            .into_iter()
+            .zip(devices)
+            .map(|(source, device)| {
+                let mut info = readings[source].clone();
+                info.device = device.clone();
+                info
+            })
+            .collect()
+    }
+
+    fn query_each(
+        &self,
+        devices: &[String],
+        idle: &BTreeMap<String, u64>,
+        hints: &BTreeMap<String, Option<String>>,
+        cached: &BTreeMap<String, DiskInfo>,
+    ) -> Vec<DiskInfo> {
+        let query_one = |device: &String| {
+            if let Some(info) = cached.get(device) {
+                return info.clone();
+            }
+            match idle.get(device.as_str()) {
+                // 空闲的设备不查询，厂商和型号取自 sysfs，与原版 hddtemp 的 SLP 记录一样带上型号
+                Some(&secs) => {
+                    let (vendor, model) = drivetemp::identity(device);
+                    DiskInfo {
+                        device: device.to_string(),
+                        vendor: vendor.or_else(|| Some("-".to_string())),
+                        model: model.or_else(|| Some("-".to_string())),
+                        status: "SKIP".to_string(),
+                        idle_seconds: Some(secs),
+                        ..Default::default()
+                    }
+                }
+                None => self.timed_query(device, hints.get(device).and_then(|t| t.as_deref())),
+            }
+        };
+        let Some(per_controller) = self.query.parallel_per_controller else {
+            return self
+                .pool
+                .install(|| devices.par_iter().map(query_one).collect());
+        };
+
+        // --parallel-per-controller：按控制器分组，组间完全并行，组内最多同时查询 N 个设备；
+        // 找不到控制器的设备各自单独成组
+        let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for (i, device) in devices.iter().enumerate() {
+            let key = resolve::controller(device).unwrap_or_else(|| device.clone());
+            groups.entry(key).or_default().push(i);
+        }
+        let mut indexed: Vec<(usize, DiskInfo)> = thread::scope(|scope| {
+            let workers: Vec<_> = groups
+                .values()
+                .map(|indices| {
+                    scope.spawn(|| {
+                        let run = || {
+                            indices
+                                .par_iter()
+                                .map(|&i| (i, query_one(&devices[i])))
+                                .collect::<Vec<_>>()
+                        };
+                        match rayon::ThreadPoolBuilder::new()
+                            .num_threads(per_controller as usize)
+                            .build()
+                        {
+                            Ok(pool) => pool.install(run),
+                            Err(_) => indices
+                                .iter()
+                                .map(|&i| (i, query_one(&devices[i])))
+                                .collect(),
+                        }
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| {
+                    worker
+                        .join()
+                        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                })
+                .collect()
+        });
+        indexed.sort_by_key(|&(i, _)| i);
+        indexed.into_iter().map(|(_, info)| info).collect()
+    }
+
+    fn timed_query(&self, device: &str, hint: Option<&str>) -> DiskInfo {
+        let started = Instant::now();
+        // 单个设备的查询 panic 时只让该设备显示为 FAIL，不影响整份报告
+        let mut info = panic::catch_unwind(AssertUnwindSafe(|| self.query(device, hint)))
+            .unwrap_or_else(|payload| {
+                self.failed(
+                    device,
+                    format!("Internal error: {}", panic_message(payload.as_ref())),
+                )
+            });
+        info.elapsed = started.elapsed();
+        info
+    }
+
+    // 没有读数且设备节点已不存在（热插拔或 udev 重命名）时重新枚举一次，
+    // 按 WWID/序列号找到设备的新名字后重试；身份未知时仅在恰好出现一个新设备时重试
+    fn retry_vanished(
+        &self,
+        results: &mut [DiskInfo],
+        devices: &[String],
+        identities: &BTreeMap<&str, String>,
+    ) {
+        let vanished = |info: &DiskInfo| {
+            info.temperature.is_none() && !std::path::Path::new(&info.device).exists()
+        };
+        if !results.iter().any(vanished) {
+            return;
+        }
+        let Ok(refreshed) = get_all_disk_devices(&self.query.discovery) else {
+            return;
+        };
+        let new_devices: Vec<&String> = refreshed.iter().filter(|d| !devices.contains(d)).collect();
+
+        for info in results.iter_mut().filter(|info| vanished(info)) {
+            let replacement = match identities.get(info.device.as_str()) {
+                Some(id) => new_devices
+                    .iter()
+                    .find(|d| resolve::device_identity(d).as_ref() == Some(id)),
+                None if new_devices.len() == 1 => new_devices.first(),
+                None => None,
+            };
+            if let Some(device) = replacement {
+                eprintln!(
+                    "{} disappeared during the scan; retrying as {device}",
+                    info.device
+                );
+                *info = self.timed_query(device, None);
+            }
+        }
+    }
+
+    // 找出空闲时间达到 --skip-idle 的设备，并把最新的 I/O 计数写回状态文件
+    fn idle_devices(&self, devices: &[String]) -> BTreeMap<String, u64> {
+        let Some(threshold) = self.query.skip_idle else {
+            return BTreeMap::new();
+        };
+        let mut state = state::State::load(&self.query.state_file);
+        let now = state::now_secs();
+        let idle = devices
+            .iter()
+            .filter_map(|device| {
+                let secs = state.observe_activity(device, now)?;
+                (secs >= threshold).then(|| (device.clone(), secs))
+            })
+            .collect();
+        if let Err(e) = state.save(&self.query.state_file) {
+            eprintln!(
+                "Failed to save state file {}: {e}",
+                self.query.state_file.display()
+            );
+        }
+        idle
+    }
+
+    // 状态优先级：SUSPECT（--sanity）> CRIT/WARN > PREFAIL/NVME-WARN > STANDBY（--power-mode）> NOTEMP（--warn-on-missing-temp）> FUZZY（--strict-json 下的文本读数）> WAKEUP > OK
+    fn status_for(
+        &self,
+        reading: &DiskReading,
+        thresholds: Option<Thresholds>,
+        was_in_standby: bool,
+    ) -> &'static str {
+        if self.query.sanity && sanity_problem(&reading.samples).is_some() {
+            "SUSPECT"
+        } else if let Some(status) = threshold_status(reading.temperature, thresholds) {
+            status
+        } else if !reading.failing_attributes().is_empty() {
+            "PREFAIL"
+        } else if !reading.nvme_critical_warnings().is_empty() {
+            "NVME-WARN"
+        } else if reading.skipped_for_power_mode() {
+            "STANDBY"
+        } else if self.query.warn_on_missing_temp && reading.temperature.is_none() {
+            "NOTEMP"
+        } else if self.query.strict_json && reading.temperature_source == Some(TempSource::Text) {
+            "FUZZY"
+        } else if was_in_standby {
+            "WAKEUP"
+        } else {
+            "OK"
+        }
+    }
+
+    // 由成功的读数生成最终结果：先计算派生字段，再移动读数中的字符串
+    fn disk_info(&self, device: &str, mut reading: DiskReading, was_in_standby: bool) -> DiskInfo {
+        // 因 --power-mode 跳过时 smartctl 不返回型号，改从 sysfs 读取
+        if reading.skipped_for_power_mode() && reading.model.is_none() {
+            (reading.vendor, reading.model) = drivetemp::identity(device);
+        }
+        let columns = self
+            .columns
+            .iter()
+            .map(|(name, path)| {
+                let value = reading.raw.as_ref().and_then(|raw| path.evaluate(raw));
+                (name.clone(), value.cloned().unwrap_or(Value::Null))
+            })
+            .collect();
+        let rated_max_c = reading
+            .model_family()
+            .and_then(|family| self.rated.lookup(family));
+        let thresholds = Thresholds::for_drive(&self.query, rated_max_c);
+        DiskInfo {
+            device: device.to_string(),
+            columns,
+            smartctl_version: reading.smartctl_version(),
+            smartctl_type: reading
+                .raw
+                .as_ref()
+                .and_then(|raw| raw["device"]["type"].as_str())
+                .map(str::to_string),
+            serial: reading.serial(),
+            wwn: reading.wwn(),
+            drive_type: reading.drive_type(),
+            capacity_bytes: reading.capacity(),
+            attribute_temperatures_c: if self.query.all_temps {
+                reading.ata_attribute_temperatures()
+            } else {
+                BTreeMap::new()
+            },
+            sensor_temperatures_c: if self.query.all_sensors {
+                reading.nvme_sensor_temperatures()
+            } else {
+                BTreeMap::new()
+            },
+            suspect: self
+                .query
+                .sanity
+                .then(|| sanity_problem(&reading.samples))
+                .flatten(),
+            failing_attributes: reading.failing_attributes(),
+            nvme_critical_warning: reading.nvme_critical_warnings(),
+            drive_trip_c: reading.drive_trip(),
+            lifetime_max_c: reading.lifetime_max(),
+            rated_max_c,
+            status: self
+                .status_for(&reading, thresholds, was_in_standby)
+                .to_string(),
+            thresholds,
+            threshold_state: thresholds.map(|t| t.evaluate(reading.temperature)),
+            temperature: reading.temperature,
+            temperature_source: reading.temperature_source,
+            hwmon_source: reading.hwmon_source,
+            unit_corrected: reading.unit_correction,
+            delta_ambient_c: self
+                .query
+                .ambient
+                .zip(reading.temperature)
+                .map(|(ambient, t)| t - ambient),
+            vendor: reading.vendor,
+            model: reading.model,
+            ..Default::default()
+        }
+    }
+
+    fn query(&self, device: &str, hint: Option<&str>) -> DiskInfo {
+        // MMC 设备、drivetemp 和 nvme-cli 不经过 smartctl，不存在唤醒问题；
+        // 待机检测依赖 smartctl 的 JSON 输出，文本模式下也不做
+        let skips_smartctl = mmc::is_mmc_device(device)
+            || match self.query.backend {
+                Backend::Drivetemp | Backend::Text => true,
+                Backend::NvmeCli => nvmecli::is_nvme_device(device),
+                _ => false,
+            };
+        let was_in_standby =
+            self.query.error_on_wakeup && !skips_smartctl && is_in_standby(device, &self.query);
+        match sample_disk(device, &self.query, hint) {
+            Ok(reading) => self.disk_info(device, reading, was_in_standby),
+            Err(e) => self.failed(device, e.to_string()),
+        }
+    }
+
+    // 查询失败的设备显示为 FAIL 行
+    fn failed(&self, device: &str, error: String) -> DiskInfo {
+        DiskInfo {
+            device: device.to_string(),
+            vendor: Some("Failed".to_string()),
+            model: Some("Failed".to_string()),
+            temperature: None,
+            status: "FAIL".to_string(),
+            error: Some(error),
+            thresholds: Thresholds::from_query(&self.query),
+            threshold_state: Thresholds::from_query(&self.query).map(|_| ThresholdState::Unknown),
+            ..Default::default()
+        }
+    }
+}
+
+// 多路径环境下同一物理盘会以多个设备出现，温度差异过大通常意味着传感器异常或时序问题
+fn warn_inconsistent_paths(results: &[DiskInfo]) {
+    let mut by_identity: BTreeMap<&str, Vec<(&str, f64)>> = BTreeMap::new();
+    for info in results {
+        if let (Some(id), Some(temp)) =
+            (info.wwn.as_ref().or(info.serial.as_ref()), info.temperature)
+        {
+            by_identity
+                .entry(id)
+                .or_default()
+                .push((&info.device, temp));
+        }
+    }
+
+    for (id, paths) in by_identity {
+        let (Some(min), Some(max)) = (
+            paths.iter().map(|&(_, t)| t).reduce(f64::min),
+            paths.iter().map(|&(_, t)| t).reduce(f64::max),
+        ) else {
+            continue;
+        };
+        if max - min > MULTIPATH_TEMP_TOLERANCE {
+            let readings: Vec<String> = paths
+                .iter()
+                .map(|(device, temp)| format!("{device}={temp}°C"))
+                .collect();
+            eprintln!(
+                "Warning: drive {id} reports inconsistent temperatures across paths: {}",
+                readings.join(", ")
+            );
+        }
+    }
+}
+
+// 假冒或固件有问题的设备常报告的固定温度值
+const BOGUS_TEMPERATURES: [f64; 4] = [0.0, 127.0, 128.0, 255.0];
+// 正常工作的硬盘可能出现的温度范围
+const PLAUSIBLE_TEMPERATURES: std::ops::RangeInclusive<f64> = 1.0..=100.0;
+
+// --sanity 检查：任一采样超出合理范围，或所有采样都是同一个已知的异常值
+fn sanity_problem(samples: &[f64]) -> Option<String> {
+    if let Some(t) = samples.iter().find(|t| !PLAUSIBLE_TEMPERATURES.contains(t)) {
+        return Some(format!("implausible temperature {t}°C"));
+    }
+    let first = *samples.first()?;
+    if BOGUS_TEMPERATURES.contains(&first) && samples.iter().all(|&t| t == first) {
+        return Some(format!(
+            "constant {first}°C across {} samples",
+            samples.len()
+        ));
+    }
+    None
+}
+
+// 根据设备的阈值判断温度状态，未越限时返回 None
+fn threshold_status(
+    temperature: Option<f64>,
+    thresholds: Option<Thresholds>,
+) -> Option<&'static str> {
+    match thresholds?.evaluate(temperature) {
+        ThresholdState::Crit => Some("CRIT"),
+        ThresholdState::Warn => Some("WARN"),
+        ThresholdState::Ok | ThresholdState::Unknown => None,
+    }
+}
+
+// 各状态对应的退出码：CRIT 为 2，WARN、各类可信度警告及健康警告为 1；
+// --warn-on-missing-temp 下读不到温度（NOTEMP，以及查询失败的 FAIL）也为 1
+fn status_severity(status: &str, query: &QueryArgs) -> i32 {
+    match status {
+        "CRIT" => 2,
+        "WARN" | "FUZZY" | "SUSPECT" | "NOTEMP" | "PREFAIL" | "NVME-WARN" => 1,
+        "FAIL" if query.warn_on_missing_temp => 1,
+        _ => 0,
+    }
+}
+
+// 退出码取所有设备中最严重的状态
+fn exit_code(results: &[DiskInfo], query: &QueryArgs) -> i32 {
+    results
+        .iter()
+        .map(|info| status_severity(&info.status, query))
+        .max()
+        .unwrap_or(0)
+}
+
+// 打印错误并退出
+fn exit_with_error(context: &str, e: impl std::fmt::Display) -> ! {
+    eprintln!("{context}: {e}");
+    std::process::exit(1);
+}
+
+// 检查是否有 root 权限
+fn require_root() {
     if !nix::unistd::Uid::effective().is_root() {
         eprintln!("Must be run as root.");
         std::process::exit(1);
     }
+}
 
-    // 获取硬盘设备列表
-    let devices = match get_all_disk_devices() {
-        Ok(d) => d,
-        Err(e) => {
-            eprintln!("Failed to get devices: {e}");
-            std::process::exit(1);
+// --remote-only 不查询本机设备，无需 root
+fn require_root_for(query: &QueryArgs) {
+    if !query.remote_only {
+        require_root();
+    }
+}
+
+// 创建扫描上下文，配置有误时退出
+fn scanner_or_exit(query: &QueryArgs) -> Scanner {
+    let scanner =
+        Scanner::new(query).unwrap_or_else(|e| exit_with_error("Invalid configuration", e));
+    // 只读 drivetemp 或只查询远程主机时用不到本机的 smartctl，文本模式本来就是为旧版准备的
+    if !query.remote_only && !matches!(query.backend, Backend::Drivetemp | Backend::Text) {
+        if let Err(e) = check_smartctl_version(&query.smartctl) {
+            exit_with_error(
+                "Incompatible smartctl",
+                format!("{e}; upgrade smartctl or use --backend text"),
+            );
         }
-    };
+    }
+    scanner
+}
 
-    // println!("Disk devices:");
+// --once 时获取运行锁；已有实例在运行则直接退出
+fn lock_or_exit(query: &QueryArgs) -> Option<lock::RunLock> {
+    if !query.once {
+        return None;
+    }
+    match lock::acquire(&query.lock_file, query.lock_wait) {
+        Ok(lock::Acquired::Locked(lock)) => Some(lock),
+        Ok(lock::Acquired::Busy(pid)) => {
+            let holder = pid.map_or(String::new(), |pid| format!(" (pid {pid})"));
+            eprintln!(
+                "Another instance{holder} holds {}; exiting.",
+                query.lock_file.display()
+            );
+            std::process::exit(0);
+        }
+        Err(e) => exit_with_error("Failed to acquire lock", e),
+    }
+}
 
-    // 并行处理每个设备，获取厂商名、硬盘型号和温度
-    let results: Vec<_> = devices
+// 设置了 --report-dir 时存档本次结果，设置了 --log-csv 时追加到 CSV，失败只打印警告
+fn archive_report(results: &[DiskInfo], scanner: &Scanner) {
+    let query = &scanner.query;
+    if let Some(dir) = &query.report_dir {
+        if let Err(e) = output::archive(dir, query.report_keep, &scanner.hostname, results) {
+            eprintln!("Failed to write report to {}: {e}", dir.display());
+        }
+    }
+    if let Some(path) = &query.log_csv {
+        let format = scanner.csv_format();
+        if let Err(e) = output::append_csv_log(path, &format, results, query.force_append) {
+            eprintln!("Not appending to {}: {e}", path.display());
+        }
+    }
+}
+
+// list 子命令：只列出设备，不查询温度
+fn run_list(args: &ListArgs) {
+    let devices = get_all_disk_devices(&args.discovery)
+        .unwrap_or_else(|e| exit_with_error("Failed to get devices", e));
+    if !args.json {
+        for device in devices {
+            println!("{device}");
+        }
+        return;
+    }
+
+    require_root();
+    if let Err(e) = check_smartctl_version(&args.smartctl) {
+        exit_with_error("Incompatible smartctl", e);
+    }
+    let entries: Vec<InventoryEntry> = devices
         .par_iter()
-        .map(|device| match get_disk_info_and_temperature(device) {
-            Ok((vendor, model, temp)) => (
-                device.to_string(),
-                vendor,
-                model,
-                temp.map_or("N/A".to_string(), |t| format!("{t}°C")),
-                "OK".to_string(),
-            ),
-            Err(e) => (
-                device.to_string(),
-                "Failed".to_string(),
-                "Failed".to_string(),
-                e.to_string(),
-                "FAIL".to_string(),
-            ),
-        })
+        .map(|device| identify_device(device, &args.smartctl))
         .collect();
+    let inventory = Inventory {
+        version: output::OUTPUT_VERSION,
+        devices: entries,
+    };
+    let printed = if args.pretty {
+        serde_json::to_string_pretty(&inventory)
+    } else {
+        serde_json::to_string(&inventory)
+    };
+    match printed {
+        Ok(text) => println!("{text}"),
+        Err(e) => exit_with_error("Failed to write output", e),
+    }
+}
+
+// --top：按温度从高到低取前 n 个有读数的设备；没有读数的设备不参与排名，在 stderr 单独列出
+fn top_hottest(results: &[DiskInfo], n: usize) -> Vec<DiskInfo> {
+    let (mut readable, unreadable): (Vec<&DiskInfo>, Vec<&DiskInfo>) =
+        results.iter().partition(|info| info.temperature.is_some());
+    readable.sort_by(|a, b| {
+        b.temperature
+            .unwrap_or_default()
+            .total_cmp(&a.temperature.unwrap_or_default())
+    });
+    if !unreadable.is_empty() {
+        let devices: Vec<&str> = unreadable.iter().map(|i| i.device.as_str()).collect();
+        eprintln!("Not ranked (no reading): {}", devices.join(", "));
+    }
+    readable.into_iter().take(n).cloned().collect()
+}
 
-    let mut table = Table::new();
-    table.set_format(
-        format::FormatBuilder::new()
-            .padding(2, 2) // 设置左右填充空格
-            .build(),
+// read 子命令：打印表格（默认模式）
+fn run_read(args: &ReadArgs) {
+    // --zabbix-item 只查询指定的设备，再按 --max-only 的形式输出
+    let zabbix_query = args.zabbix_item.as_ref().map(|device| {
+        let mut query = args.query.clone();
+        query.discovery.devices = vec![device.clone()];
+        query
+    });
+    if let Some(expr) = &args.probe {
+        // 读文件时不需要 root
+        if args.from_file.is_none() {
+            require_root();
+        }
+        match probe::run(expr, args.from_file.as_deref(), &args.query) {
+            Ok(all_found) => std::process::exit(if all_found { 0 } else { 1 }),
+            Err(e) => exit_with_error("Probe failed", e),
+        }
+    }
+    // --bench 测量的是实际查询的耗时，不沿用 --min-interval 的读数
+    let bench_query = args.bench.map(|_| QueryArgs {
+        min_interval: 0,
+        ..args.query.clone()
+    });
+    // 先检查权限：非 root 时不必先跑 smartctl --version、读配置才报错
+    require_root_for(&args.query);
+    let scanner = scanner_or_exit(
+        zabbix_query
+            .as_ref()
+            .or(bench_query.as_ref())
+            .unwrap_or(&args.query),
     );
-    table.add_row(row!["DEVICE", "VENDOR", "MODEL", "TEMP", "STATUS"]);
-    for (device, vendor, model, temp, status) in results {
-        table.add_row(Row::new(vec![
-            Cell::new(&device),
-            Cell::new(&vendor),
-            Cell::new(&model),
-            Cell::new(&temp),
-            Cell::new(&status),
-        ]));
-    }
-
-    table.printstd();
+    let lock = lock_or_exit(&args.query);
+    if let Some(runs) = args.bench {
+        if let Err(e) = bench::run(&scanner, runs) {
+            exit_with_error("Benchmark failed", e);
+        }
+        return;
+    }
+    // 先读快照，文件有误时在查询之前就报错
+    let snapshot = args.compare.as_deref().map(|path| {
+        output::Snapshot::load(path)
+            .unwrap_or_else(|e| exit_with_error("Failed to read --compare snapshot", e))
+    });
+    let results = scanner
+        .scan()
+        .unwrap_or_else(|e| exit_with_error("Failed to get devices", e));
+    archive_report(&results, &scanner);
+    if let Some(path) = &args.snapshot {
+        let json = JsonFormat {
+            pretty: true,
+            host: Some(scanner.hostname.clone()),
+        };
+        if let Err(e) = output::write_to(&path.to_string_lossy(), &json, &results) {
+            exit_with_error(&format!("Failed to write snapshot {}", path.display()), e);
+        }
+    }
+
+    // JSON 占用 stdout 时，人类可读的输出改写到 stderr 供终端查看
+    let to_stderr = args.also_json.as_deref() == Some("-");
+    let terminal_width = if to_stderr {
+        terminal_size::terminal_size_of(io::stderr())
+    } else {
+        terminal_size::terminal_size_of(io::stdout())
+    }
+    .map(|(terminal_size::Width(w), _)| w as usize);
+
+    let human: Box<dyn OutputFormat> = if args.format_preset == Some(cli::FormatPreset::Hddtemp) {
+        Box::new(HddtempLinesFormat {
+            placeholders: output::Placeholders::from_query(&args.query),
+            fahrenheit: args.fahrenheit,
+        })
+    } else if args.zabbix_discovery {
+        Box::new(ZabbixDiscoveryFormat)
+    } else if args.max_only || args.zabbix_item.is_some() {
+        Box::new(MaxOnlyFormat {
+            fahrenheit: args.fahrenheit,
+            precision: args.precision,
+        })
+    } else if args.summary_only {
+        Box::new(output::SummaryFormat {
+            fahrenheit: args.fahrenheit,
+            precision: args.precision,
+        })
+    } else if args.flat {
+        Box::new(FlatFormat {
+            with_na: args.flat_na,
+            fahrenheit: args.fahrenheit,
+            precision: args.precision,
+        })
+    } else {
+        Box::new(TableFormat {
+            columns: scanner.column_names(),
+            placeholders: output::Placeholders::from_query(&args.query),
+            max_model_width: args.max_model_width.map(|w| w as usize),
+            show_host: !args.query.remote.is_empty(),
+            show_type: args.show_type,
+            show_capacity: args.show_capacity,
+            show_rated: args.show_rated,
+            show_bus: args.show_bus,
+            compare: snapshot.as_ref(),
+            show_ambient_delta: args.query.ambient.is_some(),
+            show_note: !scanner.annotations.is_empty(),
+            group_identical: args.group_identical,
+            show_pools: args.query.pools,
+            show_age: args.watch.is_some(),
+            fahrenheit: args.fahrenheit,
+            both_units: args.show_both_units,
+            precision: args.precision,
+            timestamp: !args.no_timestamp,
+            style: if args.compact {
+                cli::TableStyle::Compact
+            } else {
+                args.table_style
+            },
+            layout: args.layout,
+            terminal_width,
+        })
+    };
+
+    if let Some(seconds) = args.watch {
+        watch(
+            &scanner,
+            human.as_ref(),
+            args,
+            results,
+            Duration::from_secs(seconds),
+        );
+    }
+
+    // --top 只影响显示；存档、--also-json 和退出码仍基于全部设备
+    let shown = match args.top {
+        Some(n) => top_hottest(&results, n as usize),
+        None => results.clone(),
+    };
+    let rendered = if to_stderr {
+        human.render(&shown, &mut io::stderr().lock())
+    } else {
+        human.render(&shown, &mut io::stdout().lock())
+    };
+    if let Err(e) = rendered {
+        exit_with_error("Failed to write output", e);
+    }
+    if let Some(target) = args.also_json.as_deref() {
+        let json = JsonFormat {
+            pretty: args.pretty,
+            host: Some(scanner.hostname.clone()),
+        };
+        if let Err(e) = output::write_to(target, &json, &results) {
+            exit_with_error(&format!("Failed to write JSON to {target}"), e);
+        }
+    }
+    let code = alert::handle(&results, &args.query);
+    // process::exit 不会运行析构函数，先手动释放锁
+    drop(lock);
+    std::process::exit(code);
+}
+
+// --watch：每隔 interval 重新扫描并重绘，直到被中断。记录每个设备上一次读到温度的时刻以计算 AGE；
+// 每轮照常存档和触发告警，但不以退出码结束
+fn watch(
+    scanner: &Scanner,
+    human: &dyn OutputFormat,
+    args: &ReadArgs,
+    mut results: Vec<DiskInfo>,
+    interval: Duration,
+) -> ! {
+    let mut last_read: BTreeMap<(Option<String>, String), Instant> = BTreeMap::new();
+    let clear = io::stdout().is_terminal();
+    // 扫描失败时重绘上一轮的结果，其读数不算作新的读数
+    let mut fresh = true;
+    // --watch-diff 下上一轮显示的结果；第一轮照常画出整个表格
+    let mut previous: Option<Vec<DiskInfo>> = None;
+    loop {
+        let now = Instant::now();
+        for info in &mut results {
+            let key = (info.host.clone(), info.device.clone());
+            if fresh && info.temperature.is_some() {
+                // --min-interval 沿用的读数按其实际读取的时刻计算 AGE
+                let cached = Duration::from_secs(info.cached_seconds.unwrap_or(0));
+                last_read.insert(key.clone(), now.checked_sub(cached).unwrap_or(now));
+            }
+            info.age = last_read.get(&key).map(|&at| now - at);
+        }
+        let shown = match args.top {
+            Some(n) => top_hottest(&results, n as usize),
+            None => results.clone(),
+        };
+        let mut out = io::stdout().lock();
+        let drawn = match &previous {
+            // 扫描失败时没有新的读数，也就没有变化可报告
+            Some(_) if !fresh => Ok(()),
+            Some(previous) => output::write_watch_changes(
+                previous,
+                &shown,
+                args.fahrenheit,
+                args.precision,
+                &mut out,
+            ),
+            // 终端上清屏后从左上角重绘，重定向到文件时各轮之间空一行
+            None => if clear {
+                write!(out, "\x1b[2J\x1b[H")
+            } else {
+                writeln!(out)
+            }
+            .and_then(|_| human.render(&shown, &mut out))
+            .and_then(|_| out.flush()),
+        };
+        if let Err(e) = drawn {
+            exit_with_error("Failed to write output", e);
+        }
+        drop(out);
+        if args.watch_diff {
+            previous = Some(shown);
+        }
+        if fresh {
+            alert::handle(&results, &args.query);
+        }
+        thread::sleep(interval);
+        fresh = match scanner.scan() {
+            Ok(scanned) => {
+                results = scanned;
+                archive_report(&results, scanner);
+                true
+            }
+            Err(e) => {
+                eprintln!("Scan failed: {e}");
+                false
+            }
+        };
+    }
+}
+
+// export 子命令：一次性输出机器可读结果
+fn run_export(args: &ExportArgs) {
+    require_root_for(&args.query);
+    let scanner = scanner_or_exit(&args.query);
+    let lock = lock_or_exit(&args.query);
+    let results = scanner
+        .scan()
+        .unwrap_or_else(|e| exit_with_error("Failed to get devices", e));
+    archive_report(&results, &scanner);
+    let format: Box<dyn OutputFormat> = match args.format {
+        ExportFormat::Json => Box::new(JsonFormat {
+            pretty: args.pretty,
+            host: Some(scanner.hostname.clone()),
+        }),
+        ExportFormat::Csv => Box::new(scanner.csv_format()),
+        ExportFormat::Openmetrics => Box::new(output::OpenMetricsFormat {
+            host: Some(scanner.hostname.clone()),
+        }),
+        #[cfg(feature = "yaml")]
+        ExportFormat::Yaml => Box::new(output::YamlFormat {
+            host: Some(scanner.hostname.clone()),
+        }),
+    };
+    if let Err(e) = output::write_to("-", format.as_ref(), &results) {
+        exit_with_error("Failed to write output", e);
+    }
+    let code = alert::handle(&results, &args.query);
+    // process::exit 不会运行析构函数，先手动释放锁
+    drop(lock);
+    std::process::exit(code);
+}
+
+// panic 携带的消息，panic!/expect 等产生的是 &str 或 String
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+// 用一行简短的错误信息代替默认的 panic 输出，不打印回溯提示
+fn install_panic_hook() {
+    panic::set_hook(Box::new(|info| {
+        let location = info
+            .location()
+            .map(|l| format!(" at {}:{}", l.file(), l.line()))
+            .unwrap_or_default();
+        eprintln!(
+            "Internal error{location}: {}",
+            panic_message(info.payload())
+        );
+    }));
+}
+
+// 主函数
+fn main() {
+    install_panic_hook();
+    let cli = Cli::parse();
+    #[cfg(feature = "update-check")]
+    if cli.check_update {
+        if let Err(e) = update::check() {
+            exit_with_error("Update check failed", e);
+        }
+        return;
+    }
+    match &cli.command {
+        Some(Commands::List(args)) => run_list(args),
+        Some(Commands::Read(args)) => run_read(args),
+        Some(Commands::Export(args)) => run_export(args),
+        Some(Commands::Daemon(args)) => {
+            require_root_for(&args.query);
+            let scanner = scanner_or_exit(&args.query);
+            if let Err(e) = daemon::run(scanner, args) {
+                exit_with_error("Daemon failed", e);
+            }
+        }
+        None => run_read(&cli.read),
+    }
 }
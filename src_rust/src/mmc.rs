@@ -0,0 +1,71 @@
+// MMC/eMMC/SD 存储的温度读取，smartctl 无法访问这类设备，改为读取内核 sysfs
+use crate::hwmon::{self, read_attr};
+use crate::{DiskReading, TempSource};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// 判断是否为 MMC 块设备（如 /dev/mmcblk0）
+pub fn is_mmc_device(device: &str) -> bool {
+    device_name(device).starts_with("mmcblk")
+}
+
+// 判断是否为 eMMC 的硬件分区（boot0/boot1/rpmb），它们与主设备共用同一芯片
+pub fn is_hardware_partition(device: &str) -> bool {
+    let name = device_name(device);
+    name.starts_with("mmcblk") && (name.contains("boot") || name.contains("rpmb"))
+}
+
+fn device_name(device: &str) -> &str {
+    device.trim_start_matches("/dev/")
+}
+
+// 根据 JEDEC/SD 协会分配的 manfid 映射常见厂商名
+fn vendor_from_manfid(manfid: &str) -> Option<&'static str> {
+    let id = u32::from_str_radix(manfid.trim_start_matches("0x"), 16).ok()?;
+    match id {
+        0x02 | 0x03 | 0x45 => Some("SanDisk"),
+        0x11 => Some("Toshiba"),
+        0x13 | 0xfe => Some("Micron"),
+        0x15 | 0x1b => Some("Samsung"),
+        0x27 => Some("Phison"),
+        0x70 => Some("Kingston"),
+        0x90 => Some("Hynix"),
+        _ => None,
+    }
+}
+
+// 读取 MMC 设备的厂商、型号和温度
+pub fn get_disk_info_and_temperature(device: &str) -> io::Result<DiskReading> {
+    let block_device = Path::new("/sys/block")
+        .join(device_name(device))
+        .join("device");
+    let card_dir = fs::canonicalize(&block_device).map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!("No sysfs entry for MMC device {device}: {e}"),
+        )
+    })?;
+
+    let vendor = read_attr(&card_dir.join("manfid"))
+        .and_then(|id| vendor_from_manfid(&id))
+        .map(str::to_string)
+        .or_else(|| read_attr(&card_dir.join("type")));
+    let model = read_attr(&card_dir.join("name"));
+
+    // 温度可能挂在卡设备本身，也可能挂在其父级 mmc_host 上
+    let found = hwmon::find_temperature(&card_dir)
+        .or_else(|| card_dir.parent().and_then(hwmon::find_temperature));
+    let temperature = found.as_ref().map(|&(t, _)| t);
+
+    Ok(DiskReading {
+        vendor,
+        model,
+        temperature_source: temperature.map(|_| TempSource::Sysfs),
+        temperature,
+        samples: Vec::new(),
+        raw: None,
+        unit_correction: None,
+        hwmon_source: found.map(|(_, source)| source),
+    })
+}
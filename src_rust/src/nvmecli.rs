@@ -0,0 +1,86 @@
+// nvme-cli 的温度读取：部分 NVMe 控制器上 smartctl 读不到健康日志，nvme smart-log 却能正常返回
+use crate::{output_within, DiskReading, TempSource};
+use serde_json::{json, Value};
+use std::io;
+use std::process::Command;
+use std::time::Duration;
+
+// 按命名惯例识别 NVMe 设备（nvme0n1、nvme0 等）
+pub fn is_nvme_device(device: &str) -> bool {
+    device.trim_start_matches("/dev/").starts_with("nvme")
+}
+
+// 运行 nvme SUBCOMMAND -o json DEVICE 并解析输出，超过 timeout 时视为失败
+fn run(subcommand: &str, device: &str, timeout: Duration) -> io::Result<Value> {
+    let mut command = Command::new("nvme");
+    command.args([subcommand, "-o", "json", device]);
+    let output = output_within(command, timeout)
+        .map_err(|e| io::Error::new(e.kind(), format!("Failed to run nvme: {e}")))?
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("nvme {subcommand} timed out after {}s", timeout.as_secs()),
+            )
+        })?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "nvme {subcommand} exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
+
+// smart-log 的温度均为开尔文，与 smartctl 一样按减 273 换算，0 表示没有读数
+fn celsius(kelvin: &Value) -> Option<i64> {
+    kelvin.as_i64().filter(|&k| k > 0).map(|k| k - 273)
+}
+
+fn parse_smart_log(smart_log: &Value) -> Option<f64> {
+    celsius(&smart_log["temperature"]).map(|t| t as f64)
+}
+
+// temperature_sensor_1..8 整理成 smartctl 的 temperature_sensors 数组，未实现的传感器为 null
+fn sensor_temperatures(smart_log: &Value) -> Vec<Option<i64>> {
+    let mut sensors: Vec<Option<i64>> = (1..=8)
+        .map(|n| celsius(&smart_log[format!("temperature_sensor_{n}")]))
+        .collect();
+    while sensors.last() == Some(&None) {
+        sensors.pop();
+    }
+    sensors
+}
+
+pub fn get_disk_info_and_temperature(device: &str, timeout: Duration) -> io::Result<DiskReading> {
+    let smart_log = run("smart-log", device, timeout)?;
+    let temperature = parse_smart_log(&smart_log);
+    // 型号和序列号来自 id-ctrl，读不到时只报告温度；两者都以空格补齐到固定长度
+    let id_ctrl = run("id-ctrl", device, timeout).unwrap_or_default();
+    let text = |key: &str| {
+        id_ctrl[key]
+            .as_str()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+    };
+    // 按 smartctl JSON 的字段名保存，类型、序列号和 critical_warning 的解读与 smartctl 的读数一致
+    let raw = json!({
+        "device": {"protocol": "NVMe"},
+        "serial_number": text("sn"),
+        "nvme_smart_health_information_log": {
+            "critical_warning": smart_log["critical_warning"],
+            "temperature_sensors": sensor_temperatures(&smart_log),
+        },
+    });
+    Ok(DiskReading {
+        vendor: None,
+        model: text("mn"),
+        temperature_source: temperature.map(|_| TempSource::NvmeCli),
+        temperature,
+        samples: Vec::new(),
+        raw: Some(raw),
+        unit_correction: None,
+        hwmon_source: None,
+    })
+}
@@ -0,0 +1,1285 @@
+// 输出格式：每种格式实现 OutputFormat，把扫描结果渲染到任意 Write
+use crate::cli::{Layout, QueryArgs, TableStyle};
+use crate::DiskInfo;
+use prettytable::format::{self, LinePosition, LineSeparator};
+use prettytable::{Cell, Row, Table};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+// 机器可读输出的结构版本，字段有不兼容变更时递增
+pub const OUTPUT_VERSION: u32 = 3;
+
+// 把一次扫描的结果渲染为某种输出格式
+pub trait OutputFormat {
+    fn render(&self, results: &[DiskInfo], out: &mut dyn Write) -> io::Result<()>;
+}
+
+// 渲染到文件，"-" 表示 stdout
+pub fn write_to(target: &str, format: &dyn OutputFormat, results: &[DiskInfo]) -> io::Result<()> {
+    let mut writer: Box<dyn Write> = if target == "-" {
+        Box::new(io::stdout().lock())
+    } else {
+        Box::new(File::create(target)?)
+    };
+    format.render(results, &mut writer)?;
+    writer.flush()
+}
+
+// --report-dir 中报告文件名的前缀与后缀
+const REPORT_PREFIX: &str = "hddtemp-";
+const REPORT_SUFFIX: &str = ".json";
+
+// Unix 秒转为 UTC 的 (年, 月, 日, 时, 分, 秒)
+fn utc_datetime(secs: u64) -> (i64, i64, i64, u64, u64, u64) {
+    let days = (secs / 86400) as i64;
+    let rem = secs % 86400;
+    // 公历日期换算（Howard Hinnant 的 civil_from_days）
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day, rem / 3600, rem % 3600 / 60, rem % 60)
+}
+
+// 紧凑的 UTC 时间戳，如 20261014T083000Z，字典序即时间顺序
+pub fn utc_timestamp(secs: u64) -> String {
+    let (year, month, day, hour, minute, second) = utc_datetime(secs);
+    format!("{year:04}{month:02}{day:02}T{hour:02}{minute:02}{second:02}Z")
+}
+
+// ISO 8601 格式的 UTC 时间，如 2026-10-14T08:30:00Z
+pub fn iso8601_utc(secs: u64) -> String {
+    let (year, month, day, hour, minute, second) = utc_datetime(secs);
+    format!("{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}Z")
+}
+
+// UTC 的时:分:秒，如 08:30:00
+fn utc_clock(secs: u64) -> String {
+    let (_, _, _, hour, minute, second) = utc_datetime(secs);
+    format!("{hour:02}:{minute:02}:{second:02}")
+}
+
+// 把本次结果写成 DIR/hddtemp-<时间戳>.json，keep 指定时只保留最新的 keep 份
+pub fn archive(
+    dir: &Path,
+    keep: Option<usize>,
+    host: &str,
+    results: &[DiskInfo],
+) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let name = format!(
+        "{REPORT_PREFIX}{}{REPORT_SUFFIX}",
+        utc_timestamp(crate::state::now_secs())
+    );
+    let path = dir.join(name);
+    write_to(
+        &path.to_string_lossy(),
+        &JsonFormat {
+            pretty: true,
+            host: Some(host.to_string()),
+        },
+        results,
+    )?;
+
+    if let Some(keep) = keep {
+        let mut reports: Vec<PathBuf> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with(REPORT_PREFIX) && n.ends_with(REPORT_SUFFIX))
+            })
+            .collect();
+        reports.sort();
+        let excess = reports.len().saturating_sub(keep);
+        for old in &reports[..excess] {
+            fs::remove_file(old)?;
+        }
+    }
+    Ok(path)
+}
+
+// --compare 读取的快照：之前保存的 JSON 报告（--snapshot、--also-json 或 --report-dir 的输出）中
+// 各设备的温度，按序列号、WWN 和设备名索引，设备名在重启后变化时仍能对上
+#[derive(Debug, Default)]
+pub struct Snapshot(BTreeMap<String, f64>);
+
+impl Snapshot {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let invalid = |e: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid snapshot {}: {e}", path.display()),
+            )
+        };
+        let report: Value =
+            serde_json::from_str(&fs::read_to_string(path)?).map_err(|e| invalid(e.to_string()))?;
+        let disks = report["disks"]
+            .as_array()
+            .ok_or_else(|| invalid("no \"disks\" array".to_string()))?;
+        let mut temperatures = BTreeMap::new();
+        for disk in disks {
+            let Some(temp) = disk["temperature_c"].as_f64() else {
+                continue;
+            };
+            for key in ["serial", "wwn", "device"] {
+                if let Some(id) = disk[key].as_str() {
+                    temperatures.insert(id.to_string(), temp);
+                }
+            }
+        }
+        Ok(Snapshot(temperatures))
+    }
+
+    fn temperature_for(&self, info: &DiskInfo) -> Option<f64> {
+        [
+            info.serial.as_deref(),
+            info.wwn.as_deref(),
+            Some(&info.device),
+        ]
+        .into_iter()
+        .flatten()
+        .find_map(|key| self.0.get(key).copied())
+    }
+}
+
+// 摄氏度转华氏度，保留一位小数
+pub fn celsius_to_fahrenheit(celsius: f64) -> f64 {
+    ((celsius * 9.0 / 5.0 + 32.0) * 10.0).round() / 10.0
+}
+
+// 按显示精度格式化温度，如 "38°C"、"38.5°C" 或 "101°F"
+fn format_temperature(celsius: f64, fahrenheit: bool, precision: usize) -> String {
+    if fahrenheit {
+        format!("{:.precision$}°F", celsius_to_fahrenheit(celsius))
+    } else {
+        format!("{celsius:.precision$}°C")
+    }
+}
+
+// 机器可读输出中的单个设备：在 DiskInfo 之外附带换算好的华氏温度
+#[derive(Serialize)]
+struct DiskRecord<'a> {
+    #[serde(flatten)]
+    info: &'a DiskInfo,
+    temperature_f: Option<f64>,
+}
+
+impl<'a> From<&'a DiskInfo> for DiskRecord<'a> {
+    fn from(info: &'a DiskInfo) -> Self {
+        DiskRecord {
+            info,
+            temperature_f: info.temperature.map(celsius_to_fahrenheit),
+        }
+    }
+}
+
+// 机器可读输出的顶层结构
+#[derive(Serialize)]
+struct Report<'a> {
+    version: u32,
+    // 生成报告的主机（--hostname），汇总多台机器的报告时据此区分
+    #[serde(skip_serializing_if = "Option::is_none")]
+    host: Option<&'a str>,
+    // 第一个成功查询所报告的 smartctl 版本，不同版本的 JSON 字段可能不同
+    #[serde(skip_serializing_if = "Option::is_none")]
+    smartctl_version: Option<&'a str>,
+    summary: Summary,
+    disks: Vec<DiskRecord<'a>>,
+}
+
+// 报告的汇总：每个设备恰好计入 ok/warn/crit/unreadable/skipped 之一，省去使用方自行遍历
+#[derive(Debug, Default, Serialize)]
+struct Summary {
+    total: usize,
+    ok: usize,
+    warn: usize,
+    crit: usize,
+    // 查询失败或没有温度
+    unreadable: usize,
+    // 因空闲（--skip-idle）或低功耗（--power-mode）跳过
+    skipped: usize,
+    max_temp_c: Option<f64>,
+    // --pools：每个池（ZFS 按顶层 vdev 细分）的汇总
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pools: Vec<PoolSummary>,
+}
+
+// 一个 ZFS vdev 或 btrfs 文件系统中各盘温度的汇总
+#[derive(Debug, Serialize)]
+struct PoolSummary {
+    pool: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vdev: Option<String>,
+    drives: usize,
+    max_temp_c: Option<f64>,
+    // 温度最高的盘
+    hottest: Option<String>,
+}
+
+// 按 (pool, vdev) 分组，保持首次出现的顺序；不属于任何池的设备不计入
+fn pool_rollup(disks: &[DiskInfo]) -> Vec<PoolSummary> {
+    let mut groups: Vec<PoolSummary> = Vec::new();
+    for info in disks {
+        let Some(pool) = &info.pool else {
+            continue;
+        };
+        let index = match groups
+            .iter()
+            .position(|g| &g.pool == pool && g.vdev == info.vdev)
+        {
+            Some(index) => index,
+            None => {
+                groups.push(PoolSummary {
+                    pool: pool.clone(),
+                    vdev: info.vdev.clone(),
+                    drives: 0,
+                    max_temp_c: None,
+                    hottest: None,
+                });
+                groups.len() - 1
+            }
+        };
+        let group = &mut groups[index];
+        group.drives += 1;
+        if let Some(t) = info.temperature {
+            if group.max_temp_c.is_none_or(|max| t > max) {
+                group.max_temp_c = Some(t);
+                group.hottest = Some(info.device.clone());
+            }
+        }
+    }
+    groups
+}
+
+impl Summary {
+    fn new(disks: &[DiskInfo]) -> Self {
+        let mut summary = Summary {
+            total: disks.len(),
+            max_temp_c: disks.iter().filter_map(|d| d.temperature).reduce(f64::max),
+            pools: pool_rollup(disks),
+            ..Default::default()
+        };
+        for info in disks {
+            let bucket = match info.status.as_str() {
+                "CRIT" => &mut summary.crit,
+                "SKIP" | "STANDBY" => &mut summary.skipped,
+                _ if info.temperature.is_none() => &mut summary.unreadable,
+                "WARN" | "FUZZY" | "SUSPECT" | "PREFAIL" | "NVME-WARN" => &mut summary.warn,
+                _ => &mut summary.ok,
+            };
+            *bucket += 1;
+        }
+        summary
+    }
+}
+
+impl<'a> Report<'a> {
+    fn new(disks: &'a [DiskInfo], host: Option<&'a str>) -> Self {
+        Report {
+            version: OUTPUT_VERSION,
+            host,
+            smartctl_version: disks.iter().find_map(|d| d.smartctl_version.as_deref()),
+            summary: Summary::new(disks),
+            disks: disks.iter().map(DiskRecord::from).collect(),
+        }
+    }
+}
+
+// 厂商、型号未能识别时人类可读输出中显示的文字（--unknown-vendor/--unknown-model）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Placeholders<'a> {
+    pub vendor: &'a str,
+    pub model: &'a str,
+}
+
+impl<'a> Placeholders<'a> {
+    pub fn from_query(query: &'a QueryArgs) -> Self {
+        Placeholders {
+            vendor: &query.unknown_vendor,
+            model: &query.unknown_model,
+        }
+    }
+
+    pub fn vendor<'b>(&'b self, info: &'b DiskInfo) -> &'b str {
+        info.vendor.as_deref().unwrap_or(self.vendor)
+    }
+
+    pub fn model<'b>(&'b self, info: &'b DiskInfo) -> &'b str {
+        info.model.as_deref().unwrap_or(self.model)
+    }
+}
+
+// 人类可读的表格
+#[derive(Debug, Default)]
+pub struct TableFormat<'a> {
+    // 追加在固定列之后的自定义列名
+    pub columns: Vec<&'a str>,
+    pub placeholders: Placeholders<'a>,
+    // MODEL 列的最大显示宽度（终端列数）
+    pub max_model_width: Option<usize>,
+    // 使用了 --remote 时在最前面显示 HOST 列，本机设备显示为 localhost
+    pub show_host: bool,
+    // 在 MODEL 之后显示 TYPE 列（HDD/SSD/NVMe）
+    pub show_type: bool,
+    // 在 TYPE 之后显示 CAPACITY 列
+    pub show_capacity: bool,
+    // --show-rated：在 TEMP 之前显示 RATED 列（额定最高温度）
+    pub show_rated: bool,
+    // 在 STATUS 之后显示 BUS 列（设备的连接位置）
+    pub show_bus: bool,
+    // --compare：显示快照中的温度（BEFORE）及变化（DELTA）
+    pub compare: Option<&'a Snapshot>,
+    // 显示 DELTA_AMB 列（高出 --ambient 的温差）
+    pub show_ambient_delta: bool,
+    // 显示 NOTE 列（--annotations 中的备注）
+    pub show_note: bool,
+    // --group-identical：合并除设备名外完全相同的行
+    pub group_identical: bool,
+    // --pools：表格之后按池和 vdev 汇总温度
+    pub show_pools: bool,
+    // --watch：在 TEMP 之后显示 AGE 列（距上一次读到温度的时间）
+    pub show_age: bool,
+    // 以华氏度显示温度
+    pub fahrenheit: bool,
+    // 同时显示摄氏度和华氏度
+    pub both_units: bool,
+    // 温度显示的小数位数
+    pub precision: usize,
+    // 在表格前打印读取时间
+    pub timestamp: bool,
+    pub style: TableStyle,
+    pub layout: Layout,
+    // 输出目标为终端时的宽度（列数），用于 --layout auto
+    pub terminal_width: Option<usize>,
+}
+
+// STATUS 列的内容：NVME-WARN 附上置位的各项，如 "NVME-WARN: temp,reliability"
+fn status_text(info: &DiskInfo) -> String {
+    if info.status == "NVME-WARN" && !info.nvme_critical_warning.is_empty() {
+        format!("{}: {}", info.status, info.nvme_critical_warning.join(","))
+    } else {
+        info.status.clone()
+    }
+}
+
+// AGE 列的时长，如 "0s"、"45s"、"3m12s"、"1h05m"
+fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3600 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+// 容量按 1024 进位显示，与 lsblk 一致（如 4 TB 的盘显示为 3.6T），整数时省略小数
+fn format_capacity(bytes: u64) -> String {
+    let mut size = bytes as f64;
+    let mut unit = "B";
+    for next in ["K", "M", "G", "T", "P", "E"] {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = next;
+    }
+    let text = format!("{size:.1}");
+    format!("{}{unit}", text.trim_end_matches(".0"))
+}
+
+// 超过最大显示宽度时截断并追加省略号；按终端显示宽度计算，CJK 等全角字符占两列
+fn truncate_with_ellipsis(text: &str, max_width: Option<usize>) -> String {
+    match max_width {
+        Some(width) if text.width() > width => {
+            let mut truncated = String::new();
+            let mut used = 0;
+            for c in text.chars() {
+                let w = c.width().unwrap_or(0);
+                // 给省略号留出一列
+                if used + w > width.saturating_sub(1) {
+                    break;
+                }
+                used += w;
+                truncated.push(c);
+            }
+            truncated.push('…');
+            truncated
+        }
+        _ => text.to_string(),
+    }
+}
+
+impl TableFormat<'_> {
+    // --show-both-units 时形如 "38°C / 100°F"，--fahrenheit 决定哪个单位在前
+    fn format_temp(&self, celsius: f64) -> String {
+        let primary = format_temperature(celsius, self.fahrenheit, self.precision);
+        if self.both_units {
+            let other = format_temperature(celsius, !self.fahrenheit, self.precision);
+            format!("{primary} / {other}")
+        } else {
+            primary
+        }
+    }
+
+    // 温差带符号显示，华氏度下按 9/5 换算差值
+    fn format_delta(&self, delta: f64) -> String {
+        let precision = self.precision;
+        if self.fahrenheit {
+            format!("{:+.precision$}°F", delta * 9.0 / 5.0)
+        } else {
+            format!("{delta:+.precision$}°C")
+        }
+    }
+
+    fn table_format(&self) -> format::TableFormat {
+        match self.style {
+            TableStyle::Default => format::FormatBuilder::new()
+                .padding(2, 2) // 设置左右填充空格
+                .build(),
+            TableStyle::Compact => *format::consts::FORMAT_CLEAN,
+            // Markdown 要求每行首尾都有 |，表头下为 |---|---|
+            TableStyle::Markdown => format::FormatBuilder::new()
+                .column_separator('|')
+                .borders('|')
+                .separator(LinePosition::Title, LineSeparator::new('-', '|', '|', '|'))
+                .padding(1, 1)
+                .build(),
+            TableStyle::Borderless => *format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR,
+        }
+    }
+
+    // 表头与各设备的行，表格和逐条记录两种布局共用
+    fn build(&self, results: &[DiskInfo]) -> (Row, Vec<Row>) {
+        let mut header = Row::empty();
+        if self.show_host {
+            header.add_cell(Cell::new("HOST"));
+        }
+        for name in ["DEVICE", "VENDOR", "MODEL"] {
+            header.add_cell(Cell::new(name));
+        }
+        if self.show_type {
+            header.add_cell(Cell::new("TYPE"));
+        }
+        if self.show_capacity {
+            header.add_cell(Cell::new("CAPACITY"));
+        }
+        if self.show_rated {
+            header.add_cell(Cell::new("RATED"));
+        }
+        header.add_cell(Cell::new("TEMP"));
+        if self.show_age {
+            header.add_cell(Cell::new("AGE"));
+        }
+        if self.compare.is_some() {
+            header.add_cell(Cell::new("BEFORE"));
+            header.add_cell(Cell::new("DELTA"));
+        }
+        if self.show_ambient_delta {
+            header.add_cell(Cell::new("DELTA_AMB"));
+        }
+        header.add_cell(Cell::new("STATUS"));
+        if self.show_bus {
+            header.add_cell(Cell::new("BUS"));
+        }
+        if self.show_note {
+            header.add_cell(Cell::new("NOTE"));
+        }
+        for name in &self.columns {
+            header.add_cell(Cell::new(name));
+        }
+        let mut rows = Vec::with_capacity(results.len());
+        for info in results {
+            let temp = match (&info.error, info.idle_seconds) {
+                (Some(e), _) => e.clone(),
+                (None, Some(secs)) => format!("idle {secs}s"),
+                (None, None) => info
+                    .temperature
+                    .map_or("N/A".to_string(), |t| self.format_temp(t)),
+            };
+            // --all-temps：在温度后附上各属性的读数，如 "38°C (194=38 190=35)"
+            let temp = if info.attribute_temperatures_c.is_empty() {
+                temp
+            } else {
+                let attrs: Vec<String> = info
+                    .attribute_temperatures_c
+                    .iter()
+                    .rev()
+                    .map(|(&id, &t)| {
+                        let t = if self.fahrenheit {
+                            celsius_to_fahrenheit(t as f64)
+                        } else {
+                            t as f64
+                        };
+                        format!("{id}={t:.0}")
+                    })
+                    .collect();
+                format!("{temp} ({})", attrs.join(" "))
+            };
+            // --all-sensors：在温度后附上 NVMe 各传感器的读数，如 "42°C (sensor1=42 sensor2=47)"
+            let temp = if info.sensor_temperatures_c.is_empty() {
+                temp
+            } else {
+                let sensors: Vec<String> = info
+                    .sensor_temperatures_c
+                    .iter()
+                    .map(|(&n, &t)| {
+                        let t = if self.fahrenheit {
+                            celsius_to_fahrenheit(t as f64)
+                        } else {
+                            t as f64
+                        };
+                        format!("sensor{n}={t:.0}")
+                    })
+                    .collect();
+                format!("{temp} ({})", sensors.join(" "))
+            };
+            let mut row = Row::empty();
+            if self.show_host {
+                row.add_cell(Cell::new(info.host.as_deref().unwrap_or("localhost")));
+            }
+            row.add_cell(Cell::new(&info.device));
+            row.add_cell(Cell::new(self.placeholders.vendor(info)));
+            row.add_cell(Cell::new(&truncate_with_ellipsis(
+                self.placeholders.model(info),
+                self.max_model_width,
+            )));
+            if self.show_type {
+                row.add_cell(Cell::new(info.drive_type.map_or("-", |t| t.as_str())));
+            }
+            if self.show_capacity {
+                row.add_cell(Cell::new(
+                    &info.capacity_bytes.map_or("-".to_string(), format_capacity),
+                ));
+            }
+            if self.show_rated {
+                row.add_cell(Cell::new(
+                    &info
+                        .rated_max_c
+                        .map_or("-".to_string(), |t| self.format_temp(t)),
+                ));
+            }
+            row.add_cell(Cell::new(&temp));
+            if self.show_age {
+                row.add_cell(Cell::new(&info.age.map_or("-".to_string(), format_age)));
+            }
+            if let Some(snapshot) = self.compare {
+                let before = snapshot.temperature_for(info);
+                row.add_cell(Cell::new(
+                    &before.map_or("-".to_string(), |t| self.format_temp(t)),
+                ));
+                let delta = before.zip(info.temperature).map(|(b, t)| t - b);
+                row.add_cell(Cell::new(
+                    &delta.map_or("-".to_string(), |d| self.format_delta(d)),
+                ));
+            }
+            if self.show_ambient_delta {
+                row.add_cell(Cell::new(
+                    &info
+                        .delta_ambient_c
+                        .map_or("-".to_string(), |d| self.format_delta(d)),
+                ));
+            }
+            row.add_cell(Cell::new(&status_text(info)));
+            if self.show_bus {
+                row.add_cell(Cell::new(info.bus.as_deref().unwrap_or("-")));
+            }
+            if self.show_note {
+                row.add_cell(Cell::new(info.note.as_deref().unwrap_or("-")));
+            }
+            for name in &self.columns {
+                row.add_cell(Cell::new(&format_column_value(info.columns.get(*name))));
+            }
+            rows.push(row);
+        }
+        if self.group_identical {
+            rows = self.group_rows(rows);
+        }
+        (header, rows)
+    }
+
+    // 除 DEVICE 外各列都相同的行合并为第一行，DEVICE 列改为数量（如 20×），保持首次出现的顺序
+    fn group_rows(&self, rows: Vec<Row>) -> Vec<Row> {
+        let device_column = usize::from(self.show_host);
+        let mut groups: Vec<(Vec<String>, Row, usize)> = Vec::new();
+        for row in rows {
+            let key: Vec<String> = row
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| i != device_column)
+                .map(|(_, cell)| cell.get_content())
+                .collect();
+            match groups.iter_mut().find(|(k, _, _)| *k == key) {
+                Some((_, _, count)) => *count += 1,
+                None => groups.push((key, row, 1)),
+            }
+        }
+        groups
+            .into_iter()
+            .map(|(_, mut row, count)| {
+                if count > 1 {
+                    // 行中必有 DEVICE 列，set_cell 不会越界
+                    let _ = row.set_cell(Cell::new(&format!("{count}×")), device_column);
+                }
+                row
+            })
+            .collect()
+    }
+
+    fn table(&self, header: Row, rows: Vec<Row>) -> Table {
+        let mut table = Table::new();
+        table.set_format(self.table_format());
+        table.set_titles(header);
+        for row in rows {
+            table.add_row(row);
+        }
+        table
+    }
+
+    // --pools 的汇总，每个 vdev 一行，如 "tank / raidz1-0: 4 drives, hottest /dev/sdc 41°C"
+    fn write_pools(&self, results: &[DiskInfo], out: &mut dyn Write) -> io::Result<()> {
+        let pools = pool_rollup(results);
+        if pools.is_empty() {
+            return Ok(());
+        }
+        writeln!(out)?;
+        for group in pools {
+            let name = match &group.vdev {
+                Some(vdev) => format!("{} / {vdev}", group.pool),
+                None => group.pool.clone(),
+            };
+            let plural = if group.drives == 1 { "" } else { "s" };
+            let hottest = match (&group.hottest, group.max_temp_c) {
+                (Some(device), Some(t)) => format!("hottest {device} {}", self.format_temp(t)),
+                _ => "no temperature".to_string(),
+            };
+            writeln!(out, "{name}: {} drive{plural}, {hottest}", group.drives)?;
+        }
+        Ok(())
+    }
+
+    // 逐条记录布局：每个设备一段，第一行为设备名，其余每行一个 "列名: 值"
+    fn write_records(&self, header: &Row, rows: &[Row], out: &mut dyn Write) -> io::Result<()> {
+        for (i, row) in rows.iter().enumerate() {
+            if i > 0 {
+                writeln!(out)?;
+            }
+            let mut cells = row.iter();
+            writeln!(
+                out,
+                "{}",
+                cells.next().map(Cell::get_content).unwrap_or_default()
+            )?;
+            for (name, cell) in header.iter().skip(1).zip(cells) {
+                writeln!(out, "  {}: {}", name.get_content(), cell.get_content())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl OutputFormat for TableFormat<'_> {
+    fn render(&self, results: &[DiskInfo], out: &mut dyn Write) -> io::Result<()> {
+        // 显示扫描时记下的时间而不是渲染时的时间（--watch 扫描失败时重绘的是上一轮的读数），
+        // 有沿用的旧读数时取最早的一个
+        if self.timestamp {
+            let taken = results
+                .iter()
+                .map(|info| info.taken_at)
+                .filter(|&t| t > 0)
+                .min()
+                .unwrap_or_else(crate::state::now_secs);
+            writeln!(out, "Readings taken at {}", iso8601_utc(taken))?;
+        }
+        let (header, rows) = self.build(results);
+        if self.layout == Layout::Records {
+            self.write_records(&header, &rows, out)?;
+        } else {
+            let rendered = self.table(header.clone(), rows.clone()).to_string();
+            // auto：表格比终端宽时改为逐条记录，避免折行
+            let too_wide = self.layout == Layout::Auto
+                && self
+                    .terminal_width
+                    .is_some_and(|width| rendered.lines().any(|line| line.width() > width));
+            if too_wide {
+                self.write_records(&header, &rows, out)?;
+            } else {
+                write!(out, "{rendered}")?;
+            }
+        }
+        if self.show_pools {
+            self.write_pools(results, out)?;
+        }
+        Ok(())
+    }
+}
+
+// 单行 "sda=38 nvme0n1=42" 格式，无读数的设备默认省略，with_na 时显示为 NA
+#[derive(Debug, Default)]
+pub struct FlatFormat {
+    pub with_na: bool,
+    pub fahrenheit: bool,
+    pub precision: usize,
+}
+
+impl OutputFormat for FlatFormat {
+    fn render(&self, results: &[DiskInfo], out: &mut dyn Write) -> io::Result<()> {
+        let precision = self.precision;
+        let convert = |t: f64| {
+            if self.fahrenheit {
+                celsius_to_fahrenheit(t)
+            } else {
+                t
+            }
+        };
+        let mut pairs: Vec<String> = Vec::new();
+        for info in results {
+            let name = info.device.trim_start_matches("/dev/");
+            match info.temperature {
+                Some(t) => pairs.push(format!("{name}={:.precision$}", convert(t))),
+                None if self.with_na => pairs.push(format!("{name}=NA")),
+                None => {}
+            }
+            // --all-sensors 的各传感器紧跟在设备之后，如 "nvme0n1:sensor1=42"
+            for (n, &t) in &info.sensor_temperatures_c {
+                pairs.push(format!(
+                    "{name}:sensor{n}={:.precision$}",
+                    convert(t as f64)
+                ));
+            }
+        }
+        writeln!(out, "{}", pairs.join(" "))
+    }
+}
+
+// --watch-diff：与上一轮相比读数或状态有变化的设备各输出一行，如 "14:03:22 /dev/sdc 45→47"，
+// 状态变化时附上 "OK→WARN"。没有读数显示为 NA，新出现或消失的设备一侧显示为 -
+pub fn write_watch_changes(
+    previous: &[DiskInfo],
+    current: &[DiskInfo],
+    fahrenheit: bool,
+    precision: usize,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    let key = |info: &DiskInfo| (info.host.clone(), info.device.clone());
+    let temp = |info: Option<&DiskInfo>| match info.map(|i| i.temperature) {
+        None => "-".to_string(),
+        Some(None) => "NA".to_string(),
+        Some(Some(t)) if fahrenheit => format!("{:.precision$}", celsius_to_fahrenheit(t)),
+        Some(Some(t)) => format!("{t:.precision$}"),
+    };
+    let status = |info: Option<&DiskInfo>| info.map_or("-".to_string(), status_text);
+    let before: BTreeMap<_, &DiskInfo> = previous.iter().map(|i| (key(i), i)).collect();
+    let after: BTreeMap<_, &DiskInfo> = current.iter().map(|i| (key(i), i)).collect();
+    // 先按本轮的顺序，再列出本轮已消失的设备
+    let keys = current
+        .iter()
+        .map(key)
+        .chain(previous.iter().map(key).filter(|k| !after.contains_key(k)));
+    let clock = utc_clock(crate::state::now_secs());
+    for k in keys {
+        let (old, new) = (before.get(&k).copied(), after.get(&k).copied());
+        let (old_temp, new_temp) = (temp(old), temp(new));
+        let (old_status, new_status) = (status(old), status(new));
+        if old_temp == new_temp && old_status == new_status {
+            continue;
+        }
+        let name = match &k.0 {
+            Some(host) => format!("{host}:{}", k.1),
+            None => k.1.clone(),
+        };
+        write!(out, "{clock} {name} {old_temp}→{new_temp}")?;
+        if old_status != new_status {
+            write!(out, " {old_status}→{new_status}")?;
+        }
+        writeln!(out)?;
+    }
+    out.flush()
+}
+
+// --max-only：只输出所有设备中的最高温度，没有任何读数时不输出
+#[derive(Debug, Default)]
+pub struct MaxOnlyFormat {
+    pub fahrenheit: bool,
+    pub precision: usize,
+}
+
+impl OutputFormat for MaxOnlyFormat {
+    fn render(&self, results: &[DiskInfo], out: &mut dyn Write) -> io::Result<()> {
+        let Some(max) = results
+            .iter()
+            .filter_map(|info| info.temperature)
+            .reduce(f64::max)
+        else {
+            eprintln!("No drive reported a temperature");
+            return Ok(());
+        };
+        let max = if self.fahrenheit {
+            celsius_to_fahrenheit(max)
+        } else {
+            max
+        };
+        writeln!(out, "{max:.precision$}", precision = self.precision)
+    }
+}
+
+// --summary-only：只输出一行汇总，如 "8 disks, 8 readable, max 46°C, 1 warn, 0 crit"，
+// 有设备因 --skip-idle/--power-mode 被跳过时在末尾附上其数量
+#[derive(Debug, Default)]
+pub struct SummaryFormat {
+    pub fahrenheit: bool,
+    pub precision: usize,
+}
+
+impl OutputFormat for SummaryFormat {
+    fn render(&self, results: &[DiskInfo], out: &mut dyn Write) -> io::Result<()> {
+        let summary = Summary::new(results);
+        let readable = results.iter().filter(|i| i.temperature.is_some()).count();
+        let max = summary.max_temp_c.map_or("N/A".to_string(), |t| {
+            format_temperature(t, self.fahrenheit, self.precision)
+        });
+        write!(
+            out,
+            "{} disks, {readable} readable, max {max}, {} warn, {} crit",
+            summary.total, summary.warn, summary.crit
+        )?;
+        if summary.skipped > 0 {
+            write!(out, ", {} skipped", summary.skipped)?;
+        }
+        writeln!(out)
+    }
+}
+
+// 原版 hddtemp 命令的逐行输出，与其标点完全一致，温度为整数：
+// "/dev/sda: MODEL: 38°C"；没有读数时为 "no sensor" 或 "drive is sleeping"，查询失败时为 "/dev/sda: 错误信息"
+#[derive(Debug, Default)]
+pub struct HddtempLinesFormat<'a> {
+    pub placeholders: Placeholders<'a>,
+    pub fahrenheit: bool,
+}
+
+impl OutputFormat for HddtempLinesFormat<'_> {
+    fn render(&self, results: &[DiskInfo], out: &mut dyn Write) -> io::Result<()> {
+        for info in results {
+            let model = self.placeholders.model(info);
+            match (info.temperature, &info.error) {
+                (Some(t), _) => writeln!(
+                    out,
+                    "{}: {model}: {}",
+                    info.device,
+                    format_temperature(t, self.fahrenheit, 0)
+                )?,
+                (None, Some(e)) => writeln!(out, "{}: {e}", info.device)?,
+                (None, None) if info.idle_seconds.is_some() || info.status == "STANDBY" => {
+                    writeln!(out, "{}: {model}: drive is sleeping", info.device)?
+                }
+                (None, None) => writeln!(out, "{}: {model}: no sensor", info.device)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// Zabbix 低级发现（LLD）格式，只列出能读到温度（或因空闲被跳过）的设备；宏名固定为 {#DEVICE} 等
+#[derive(Debug, Default)]
+pub struct ZabbixDiscoveryFormat;
+
+impl OutputFormat for ZabbixDiscoveryFormat {
+    fn render(&self, results: &[DiskInfo], out: &mut dyn Write) -> io::Result<()> {
+        let data: Vec<Value> = results
+            .iter()
+            .filter(|info| info.temperature.is_some() || info.idle_seconds.is_some())
+            .map(|info| {
+                let mut entry = serde_json::Map::new();
+                entry.insert("{#DEVICE}".to_string(), info.device.clone().into());
+                entry.insert(
+                    "{#VENDOR}".to_string(),
+                    info.vendor.clone().unwrap_or_default().into(),
+                );
+                entry.insert(
+                    "{#MODEL}".to_string(),
+                    info.model.clone().unwrap_or_default().into(),
+                );
+                if let Some(serial) = &info.serial {
+                    entry.insert("{#SERIAL}".to_string(), serial.clone().into());
+                }
+                if let Some(wwn) = &info.wwn {
+                    entry.insert("{#WWN}".to_string(), wwn.clone().into());
+                }
+                Value::Object(entry)
+            })
+            .collect();
+        serde_json::to_writer(&mut *out, &serde_json::json!({ "data": data }))?;
+        writeln!(out)
+    }
+}
+
+// 自定义列在表格中的显示形式
+fn format_column_value(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => "N/A".to_string(),
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    }
+}
+
+// JSON 报告，默认紧凑格式便于管道处理
+#[derive(Debug, Default)]
+pub struct JsonFormat {
+    pub pretty: bool,
+    pub host: Option<String>,
+}
+
+impl OutputFormat for JsonFormat {
+    fn render(&self, results: &[DiskInfo], out: &mut dyn Write) -> io::Result<()> {
+        let report = Report::new(results, self.host.as_deref());
+        if self.pretty {
+            serde_json::to_writer_pretty(&mut *out, &report)?;
+        } else {
+            serde_json::to_writer(&mut *out, &report)?;
+        }
+        writeln!(out)
+    }
+}
+
+// YAML 报告，结构与 JSON 相同
+#[cfg(feature = "yaml")]
+#[derive(Debug, Default)]
+pub struct YamlFormat {
+    pub host: Option<String>,
+}
+
+#[cfg(feature = "yaml")]
+impl OutputFormat for YamlFormat {
+    fn render(&self, results: &[DiskInfo], out: &mut dyn Write) -> io::Result<()> {
+        serde_yaml::to_writer(out, &Report::new(results, self.host.as_deref()))
+            .map_err(io::Error::other)
+    }
+}
+
+// CSV 字段转义：包含分隔符、引号或换行时加引号
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// CSV，第一行为表头，自定义列追加在末尾
+#[derive(Debug, Default)]
+pub struct CsvFormat<'a> {
+    pub columns: Vec<&'a str>,
+    // 设置时在最前面加上 host 列：本机设备为该名称（--hostname），--remote 的设备为其主机名
+    pub host: Option<String>,
+}
+
+impl CsvFormat<'_> {
+    fn header(&self) -> Vec<&str> {
+        let mut header = Vec::new();
+        if self.host.is_some() {
+            header.push("host");
+        }
+        header.extend([
+            "device",
+            "vendor",
+            "model",
+            "serial",
+            "wwn",
+            "temperature_c",
+            "temperature_f",
+            "status",
+            "error",
+        ]);
+        header.extend(&self.columns);
+        header
+    }
+
+    fn fields(&self, info: &DiskInfo) -> Vec<String> {
+        let mut fields = Vec::new();
+        if let Some(host) = &self.host {
+            fields.push(info.host.clone().unwrap_or_else(|| host.clone()));
+        }
+        fields.extend([
+            info.device.clone(),
+            info.vendor.clone().unwrap_or_default(),
+            info.model.clone().unwrap_or_default(),
+            info.serial.clone().unwrap_or_default(),
+            info.wwn.clone().unwrap_or_default(),
+            info.temperature.map(|t| t.to_string()).unwrap_or_default(),
+            info.temperature
+                .map(|t| celsius_to_fahrenheit(t).to_string())
+                .unwrap_or_default(),
+            info.status.clone(),
+            info.error.clone().unwrap_or_default(),
+        ]);
+        for name in &self.columns {
+            fields.push(match info.columns.get(*name) {
+                None | Some(Value::Null) => String::new(),
+                value => format_column_value(value),
+            });
+        }
+        fields
+    }
+}
+
+fn csv_line<S: AsRef<str>>(fields: &[S]) -> String {
+    fields
+        .iter()
+        .map(|f| csv_field(f.as_ref()))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+impl OutputFormat for CsvFormat<'_> {
+    fn render(&self, results: &[DiskInfo], out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "{}", csv_line(&self.header()))?;
+        for info in results {
+            writeln!(out, "{}", csv_line(&self.fields(info)))?;
+        }
+        Ok(())
+    }
+}
+
+// OpenMetrics 指标名的前缀
+const METRIC_PREFIX: &str = "hddtemp_drive";
+
+// export --format openmetrics：每个指标族依次给出 # TYPE/# UNIT/# HELP 和各设备的样本，以 # EOF 结尾。
+// 温度类指标名按规范以单位 _celsius 结尾；读不到温度的设备没有温度样本，只在 _up 中记为 0
+#[derive(Debug, Default)]
+pub struct OpenMetricsFormat {
+    // 本机设备的 host 标签（--hostname），--remote 的设备用其主机名
+    pub host: Option<String>,
+}
+
+// 标签值中的反斜杠、双引号和换行需要转义
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn metric_labels(info: &DiskInfo, local_host: Option<&str>) -> String {
+    let mut labels = Vec::new();
+    if let Some(host) = info.host.as_deref().or(local_host) {
+        labels.push(("host", host));
+    }
+    labels.push(("device", info.device.as_str()));
+    for (name, value) in [("model", &info.model), ("serial", &info.serial)] {
+        if let Some(value) = value {
+            labels.push((name, value.as_str()));
+        }
+    }
+    let labels: Vec<String> = labels
+        .into_iter()
+        .map(|(name, value)| format!("{name}=\"{}\"", escape_label_value(value)))
+        .collect();
+    labels.join(",")
+}
+
+impl OutputFormat for OpenMetricsFormat {
+    fn render(&self, results: &[DiskInfo], out: &mut dyn Write) -> io::Result<()> {
+        type Sample = fn(&DiskInfo) -> Option<f64>;
+        let families: [(&str, Option<&str>, &str, Sample); 3] = [
+            (
+                "temperature_celsius",
+                Some("celsius"),
+                "Current drive temperature.",
+                |info| info.temperature,
+            ),
+            (
+                "rated_max_temperature_celsius",
+                Some("celsius"),
+                "Rated maximum operating temperature of the drive's model family.",
+                |info| info.rated_max_c,
+            ),
+            (
+                "up",
+                None,
+                "Whether a temperature was read from the drive (1) or not (0).",
+                |info| Some(if info.temperature.is_some() { 1.0 } else { 0.0 }),
+            ),
+        ];
+        for (suffix, unit, help, sample) in families {
+            let name = format!("{METRIC_PREFIX}_{suffix}");
+            writeln!(out, "# TYPE {name} gauge")?;
+            if let Some(unit) = unit {
+                writeln!(out, "# UNIT {name} {unit}")?;
+            }
+            writeln!(out, "# HELP {name} {help}")?;
+            for info in results {
+                if let Some(value) = sample(info) {
+                    let labels = metric_labels(info, self.host.as_deref());
+                    writeln!(out, "{name}{{{labels}}} {value}")?;
+                }
+            }
+        }
+        writeln!(out, "# EOF")
+    }
+}
+
+// --log-csv：把本次结果追加到 CSV 时间序列，每行前加一列读取时间。
+// 文件已有表头时必须与本次的列完全一致，否则拒绝追加（除非 force），避免改了参数后历史数据的列错位
+pub fn append_csv_log(
+    path: &Path,
+    format: &CsvFormat,
+    results: &[DiskInfo],
+    force: bool,
+) -> io::Result<()> {
+    let mut header = vec!["timestamp"];
+    header.extend(format.header());
+    let header = csv_line(&header);
+    // 只读第一行：日志会不断增长，后面的内容也不必是合法的 UTF-8
+    let existing = match File::open(path) {
+        Ok(file) => {
+            let mut first = String::new();
+            BufReader::new(file).read_line(&mut first)?;
+            let first = first.trim_end_matches(['\n', '\r']);
+            (!first.is_empty()).then(|| first.to_string())
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e),
+    };
+    if let Some(existing) = existing.as_ref().filter(|h| **h != header && !force) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "existing columns ({existing}) differ from this run's ({header}); \
+                 use --force-append to append anyway"
+            ),
+        ));
+    }
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    let mut lines = String::new();
+    if existing.is_none() {
+        lines.push_str(&header);
+        lines.push('\n');
+    }
+    let timestamp = iso8601_utc(crate::state::now_secs());
+    for info in results {
+        let mut fields = vec![timestamp.clone()];
+        fields.extend(format.fields(info));
+        lines.push_str(&csv_line(&fields));
+        lines.push('\n');
+    }
+    // 一次写入整批，减少与其他写入者交错的机会
+    file.write_all(lines.as_bytes())
+}
+
+#[cfg(test)]
+pub(crate) fn sample_disks() -> Vec<DiskInfo> {
+    let disk =
+        |device: &str, model: Option<&str>, temperature: Option<f64>, status: &str| DiskInfo {
+            device: device.to_string(),
+            model: model.map(str::to_string),
+            temperature,
+            status: status.to_string(),
+            ..Default::default()
+        };
+    vec![
+        disk("/dev/sda", Some("ST4000VN008-2DR166"), Some(36.0), "OK"),
+        DiskInfo {
+            idle_seconds: Some(1800),
+            ..disk("/dev/sdb", Some("WDC WD40EFRX-68N32N0"), None, "SKIP")
+        },
+        disk("/dev/sdc", None, None, "OK"),
+        DiskInfo {
+            error: Some("Failed for device: /dev/sdd".to_string()),
+            ..disk("/dev/sdd", None, None, "FAIL")
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(format: &dyn OutputFormat, disks: &[DiskInfo]) -> String {
+        let mut out = Vec::new();
+        format.render(disks, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn hddtemp_preset_lines() {
+        let format = HddtempLinesFormat {
+            placeholders: Placeholders {
+                vendor: "Unknown Vendor",
+                model: "Unknown Model",
+            },
+            fahrenheit: false,
+        };
+        assert_eq!(
+            render(&format, &sample_disks()),
+            "/dev/sda: ST4000VN008-2DR166: 36°C\n\
+             /dev/sdb: WDC WD40EFRX-68N32N0: drive is sleeping\n\
+             /dev/sdc: Unknown Model: no sensor\n\
+             /dev/sdd: Failed for device: /dev/sdd\n"
+        );
+    }
+
+    #[test]
+    fn table_timestamp_is_the_scan_time() {
+        let mut disks = sample_disks();
+        for info in &mut disks {
+            info.taken_at = 1_800_000_000;
+        }
+        // --min-interval 沿用的读数更早，标题取最早的时间
+        disks[1].taken_at = 1_799_999_940;
+        let format = TableFormat {
+            timestamp: true,
+            ..Default::default()
+        };
+        assert!(render(&format, &disks).starts_with("Readings taken at 2027-01-15T07:59:00Z\n"));
+    }
+
+    #[test]
+    fn openmetrics_units_and_eof() {
+        let mut disks = sample_disks();
+        disks[0].rated_max_c = Some(70.0);
+        disks[0].serial = Some("ZDH1\"AB".to_string());
+        let text = render(
+            &OpenMetricsFormat {
+                host: Some("nas01".to_string()),
+            },
+            &disks,
+        );
+        assert!(text.ends_with("\n# EOF\n"));
+        assert_eq!(text.matches("# EOF").count(), 1);
+        // 声明了单位的指标族，名称必须以 _<unit> 结尾
+        let units: Vec<&str> = text.lines().filter(|l| l.starts_with("# UNIT ")).collect();
+        assert_eq!(units.len(), 2);
+        for line in units {
+            let [_, _, name, unit] = line.split(' ').collect::<Vec<_>>()[..] else {
+                panic!("malformed UNIT line {line:?}");
+            };
+            assert!(name.ends_with(&format!("_{unit}")), "{line}");
+        }
+        assert!(text.contains(
+            "hddtemp_drive_temperature_celsius{host=\"nas01\",device=\"/dev/sda\",\
+             model=\"ST4000VN008-2DR166\",serial=\"ZDH1\\\"AB\"} 36\n"
+        ));
+        assert!(text.contains(
+            "hddtemp_drive_rated_max_temperature_celsius{host=\"nas01\",device=\"/dev/sda\""
+        ));
+        // 没有读数的设备没有温度样本，只在 _up 中记为 0
+        assert!(!text.contains("temperature_celsius{host=\"nas01\",device=\"/dev/sdc\""));
+        assert!(text.contains("hddtemp_drive_up{host=\"nas01\",device=\"/dev/sdc\"} 0\n"));
+    }
+}
@@ -0,0 +1,72 @@
+// 持久内存（Optane PMem 等 NVDIMM）的温度读取：smartctl 不支持这类设备，改为解析 ndctl list 的健康信息
+use crate::{DiskReading, TempSource};
+use serde_json::Value;
+use std::io;
+use std::process::Command;
+
+// 持久内存的 /dev/pmem* 块设备是 DIMM 上的命名空间，温度已由对应的 nmem 设备报告
+pub fn is_pmem_namespace(device: &str) -> bool {
+    device.trim_start_matches("/dev/").starts_with("pmem")
+}
+
+// ndctl 的 id 形如 "8089-a2-1837-00000bb3"，开头为 JEDEC 厂商号
+fn vendor_from_id(id: &str) -> Option<&'static str> {
+    match id.split('-').next()? {
+        "8089" => Some("Intel"),
+        "802c" => Some("Micron"),
+        "80ce" => Some("Samsung"),
+        "80ad" => Some("SK hynix"),
+        _ => None,
+    }
+}
+
+// 单个 DIMM 的读数：优先使用介质温度，没有时退回控制器温度
+fn reading_for(dimm: Value) -> DiskReading {
+    let health = &dimm["health"];
+    let temperature = health["temperature_celsius"]
+        .as_f64()
+        .or_else(|| health["controller_temperature_celsius"].as_f64());
+    DiskReading {
+        vendor: dimm["id"]
+            .as_str()
+            .and_then(vendor_from_id)
+            .map(str::to_string),
+        model: None,
+        temperature_source: temperature.map(|_| TempSource::Ndctl),
+        temperature,
+        samples: temperature.into_iter().collect(),
+        raw: Some(dimm),
+        unit_correction: None,
+        hwmon_source: None,
+    }
+}
+
+// 运行 ndctl list --dimms --health，返回每个 DIMM 的设备名（如 nmem0）和读数
+pub fn list_dimms() -> io::Result<Vec<(String, DiskReading)>> {
+    let output = Command::new("ndctl")
+        .args(["list", "--dimms", "--health"])
+        .output()
+        .map_err(|e| io::Error::new(e.kind(), format!("Failed to run ndctl: {e}")))?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "ndctl exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    // 没有 DIMM 时 ndctl 不输出任何内容；只有一个时输出对象而不是数组
+    if output.stdout.iter().all(u8::is_ascii_whitespace) {
+        return Ok(Vec::new());
+    }
+    let dimms = match serde_json::from_slice::<Value>(&output.stdout)? {
+        Value::Array(dimms) => dimms,
+        dimm => vec![dimm],
+    };
+    Ok(dimms
+        .into_iter()
+        .filter_map(|dimm| {
+            let device = dimm["dev"].as_str()?.to_string();
+            Some((device, reading_for(dimm)))
+        })
+        .collect())
+}
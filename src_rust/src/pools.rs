@@ -0,0 +1,234 @@
+// --pools：从 zpool status 和 btrfs filesystem show 读取存储池的成员盘，按池和 vdev 汇总温度
+use crate::resolve;
+use std::process::Command;
+
+// 一块盘在存储池中的位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Membership {
+    pub pool: String,
+    // ZFS 的 vdev（如 raidz1-0、mirror-1、logs 下的 mirror-2）；单盘 vdev 和 btrfs 为 None
+    pub vdev: Option<String>,
+    // 整块磁盘的 /dev 路径
+    pub device: String,
+}
+
+// ZFS 配置中不属于数据 vdev 的分组
+const ZFS_CLASSES: [&str; 5] = ["logs", "cache", "spares", "special", "dedup"];
+
+// 解析 zpool status -P 的输出。config 段按相对表头的缩进分级：第 0 级为池名或 logs 等分组，
+// 之后非路径的名字为 vdev（replacing-N 等可再嵌套），以 / 开头的为成员盘（-P 保证叶子总是完整路径）。
+// 成员盘归入其所在的顶层 vdev，直接挂在池下的盘是单盘 vdev
+pub fn parse_zpool_status(text: &str) -> Vec<(String, Option<String>, String)> {
+    let mut members = Vec::new();
+    let mut pool = None;
+    // config 段表头的缩进，不在 config 段时为 None
+    let mut base = None;
+    let mut class: Option<&str> = None;
+    let mut vdevs: Vec<(usize, &str)> = Vec::new();
+    for line in text.lines() {
+        let trimmed = line.trim();
+        let indent = line.len() - line.trim_start().len();
+        if let Some(name) = trimmed.strip_prefix("pool:") {
+            pool = Some(name.trim().to_string());
+            base = None;
+            continue;
+        }
+        if trimmed.starts_with("NAME ") {
+            base = Some(indent);
+            class = None;
+            vdevs.clear();
+            continue;
+        }
+        // 表格以空行结束，之后是 errors: 等其他段落
+        if trimmed.is_empty() || trimmed.ends_with(':') || trimmed.starts_with("errors:") {
+            base = None;
+            continue;
+        }
+        let (Some(pool_name), Some(base)) = (&pool, base) else {
+            continue;
+        };
+        let depth = indent.saturating_sub(base);
+        let Some(name) = trimmed.split_whitespace().next() else {
+            continue;
+        };
+        if depth == 0 {
+            class = ZFS_CLASSES
+                .iter()
+                .copied()
+                .find(|&c| c == name && c != pool_name);
+            vdevs.clear();
+            continue;
+        }
+        while vdevs.last().is_some_and(|&(level, _)| level >= depth) {
+            vdevs.pop();
+        }
+        if !name.starts_with('/') {
+            vdevs.push((depth, name));
+            continue;
+        }
+        let top = vdevs.first().map(|&(_, v)| v);
+        let vdev = match (class, top) {
+            (Some(class), Some(v)) => Some(format!("{class}/{v}")),
+            (Some(class), None) => Some(class.to_string()),
+            (None, v) => v.map(str::to_string),
+        };
+        members.push((pool_name.clone(), vdev, name.to_string()));
+    }
+    members
+}
+
+// 解析 btrfs filesystem show：每个文件系统以 Label 行开头（无标签时用 uuid），devid 行给出成员盘
+pub fn parse_btrfs_show(text: &str) -> Vec<(String, String)> {
+    let mut members = Vec::new();
+    let mut fs_name = None;
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if let Some(rest) = trimmed.strip_prefix("Label:") {
+            let (label, uuid) = rest.split_once("uuid:").unwrap_or((rest, ""));
+            let label = label.trim().trim_matches('\'');
+            fs_name = Some(if label.is_empty() || label == "none" {
+                uuid.trim().to_string()
+            } else {
+                label.to_string()
+            });
+        } else if trimmed.starts_with("devid") {
+            if let (Some(name), Some(path)) = (&fs_name, trimmed.split(" path ").nth(1)) {
+                members.push((name.clone(), path.trim().to_string()));
+            }
+        }
+    }
+    members
+}
+
+// 命令不存在或失败时返回 None：没有使用 ZFS/btrfs 很常见，不算错误
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// 读取所有 ZFS 池和 btrfs 文件系统的成员盘，分区映射到整块磁盘
+pub fn memberships() -> Vec<Membership> {
+    let mut members: Vec<Membership> = Vec::new();
+    if let Some(text) = run("zpool", &["status", "-P", "-L"]) {
+        for (pool, vdev, path) in parse_zpool_status(&text) {
+            members.push(Membership {
+                pool,
+                vdev,
+                device: resolve::disk_of(&path),
+            });
+        }
+    }
+    if let Some(text) = run("btrfs", &["filesystem", "show"]) {
+        for (pool, path) in parse_btrfs_show(&text) {
+            members.push(Membership {
+                pool,
+                vdev: None,
+                device: resolve::disk_of(&path),
+            });
+        }
+    }
+    members
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // zpool status -P -L 的实际输出（表格以制表符加空格缩进），含单盘 vdev、
+    // logs/cache 分组和 replacing 嵌套
+    const ZPOOL_STATUS: &str = "  pool: backup
+ state: DEGRADED
+status: One or more devices is currently being resilvered.
+  scan: resilver in progress since Tue Oct 14 21:03:11 2025
+config:
+
+\tNAME                STATE     READ WRITE CKSUM
+\tbackup              DEGRADED     0     0     0
+\t  mirror-0          DEGRADED     0     0     0
+\t    replacing-0     DEGRADED     0     0     0
+\t      /dev/sdg1     UNAVAIL      0     0     0
+\t      /dev/sdh1     ONLINE       0     0     0  (resilvering)
+\t    /dev/sdi1       ONLINE       0     0     0
+
+errors: No known data errors
+
+  pool: tank
+ state: ONLINE
+  scan: scrub repaired 0B in 05:12:33 with 0 errors on Sun Oct 12 05:36:34 2025
+config:
+
+\tNAME                STATE     READ WRITE CKSUM
+\ttank                ONLINE       0     0     0
+\t  raidz1-0          ONLINE       0     0     0
+\t    /dev/sda1       ONLINE       0     0     0
+\t    /dev/sdb1       ONLINE       0     0     0
+\t  mirror-1          ONLINE       0     0     0
+\t    /dev/sdd1       ONLINE       0     0     0
+\t    /dev/sde1       ONLINE       0     0     0
+\tlogs
+\t  /dev/nvme0n1p1    ONLINE       0     0     0
+\tcache
+\t  /dev/nvme1n1p1    ONLINE       0     0     0
+
+errors: No known data errors
+
+  pool: rpool
+ state: ONLINE
+config:
+
+\tNAME           STATE     READ WRITE CKSUM
+\trpool          ONLINE       0     0     0
+\t  /dev/sdf3    ONLINE       0     0     0
+
+errors: No known data errors
+";
+
+    const BTRFS_SHOW: &str = "Label: 'data'  uuid: 4f3c9a1e-2b7d-4c1a-9e8f-0a1b2c3d4e5f
+\tTotal devices 2 FS bytes used 1.21TiB
+\tdevid    1 size 3.64TiB used 1.22TiB path /dev/sdj
+\tdevid    2 size 3.64TiB used 1.22TiB path /dev/sdk
+
+Label: none  uuid: 9a8b7c6d-5e4f-4a3b-2c1d-0e9f8a7b6c5d
+\tTotal devices 1 FS bytes used 20.00GiB
+\tdevid    1 size 50.00GiB used 22.02GiB path /dev/nvme0n1p2
+
+";
+
+    #[test]
+    fn zpool_members_grouped_by_top_level_vdev() {
+        let member = |pool: &str, vdev: Option<&str>, path: &str| {
+            (pool.to_string(), vdev.map(str::to_string), path.to_string())
+        };
+        assert_eq!(
+            parse_zpool_status(ZPOOL_STATUS),
+            [
+                member("backup", Some("mirror-0"), "/dev/sdg1"),
+                member("backup", Some("mirror-0"), "/dev/sdh1"),
+                member("backup", Some("mirror-0"), "/dev/sdi1"),
+                member("tank", Some("raidz1-0"), "/dev/sda1"),
+                member("tank", Some("raidz1-0"), "/dev/sdb1"),
+                member("tank", Some("mirror-1"), "/dev/sdd1"),
+                member("tank", Some("mirror-1"), "/dev/sde1"),
+                member("tank", Some("logs"), "/dev/nvme0n1p1"),
+                member("tank", Some("cache"), "/dev/nvme1n1p1"),
+                member("rpool", None, "/dev/sdf3"),
+            ]
+        );
+    }
+
+    #[test]
+    fn btrfs_members_use_label_or_uuid() {
+        let member = |fs: &str, path: &str| (fs.to_string(), path.to_string());
+        assert_eq!(
+            parse_btrfs_show(BTRFS_SHOW),
+            [
+                member("data", "/dev/sdj"),
+                member("data", "/dev/sdk"),
+                member("9a8b7c6d-5e4f-4a3b-2c1d-0e9f8a7b6c5d", "/dev/nvme0n1p2"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,87 @@
+// --probe：在 smartctl 的 JSON 输出上求值一个路径并打印结果，用于查找自定义列该写的路径
+use crate::cli::QueryArgs;
+use crate::config::JsonPath;
+use serde_json::Value;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// 以 / 开头按 JSON Pointer（RFC 6901）求值，否则按配置文件中自定义列的路径语法求值
+enum Probe {
+    Pointer(String),
+    Path(JsonPath),
+}
+
+impl Probe {
+    fn parse(expr: &str) -> io::Result<Probe> {
+        if expr.is_empty() || expr.starts_with('/') {
+            return Ok(Probe::Pointer(expr.to_string()));
+        }
+        JsonPath::parse(expr)
+            .map(Probe::Path)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("--probe: {e}")))
+    }
+
+    fn evaluate<'a>(&self, root: &'a Value) -> Option<&'a Value> {
+        match self {
+            Probe::Pointer(pointer) => root.pointer(pointer),
+            Probe::Path(path) => path.evaluate(root),
+        }
+    }
+}
+
+// 标量直接打印，对象和数组缩进打印，便于继续向下查找
+fn render(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Object(_) | Value::Array(_) => {
+            serde_json::to_string_pretty(value).unwrap_or_default()
+        }
+        other => other.to_string(),
+    }
+}
+
+// 对 --from-file 指定的 JSON 文件或每个设备的 smartctl 输出求值；返回是否所有输入都找到了值
+pub fn run(expr: &str, from_file: Option<&Path>, query: &QueryArgs) -> io::Result<bool> {
+    let probe = Probe::parse(expr)?;
+    if let Some(path) = from_file {
+        let content = fs::read_to_string(path)?;
+        let root: Value = serde_json::from_str(&content).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} is not JSON: {e}", path.display()),
+            )
+        })?;
+        return Ok(match probe.evaluate(&root) {
+            Some(value) => {
+                println!("{}", render(value));
+                true
+            }
+            None => {
+                eprintln!("No value at {expr}");
+                false
+            }
+        });
+    }
+
+    let mut all_found = true;
+    for device in crate::get_all_disk_devices(&query.discovery)? {
+        let value = crate::get_disk_info_and_temperature(&device, query, None)
+            .ok()
+            .and_then(|reading| reading.raw)
+            .and_then(|raw| probe.evaluate(&raw).cloned());
+        match value {
+            // 多行的值（对象、数组）另起一行打印
+            Some(value) => {
+                let text = render(&value);
+                let sep = if text.contains('\n') { "\n" } else { " " };
+                println!("{device}:{sep}{text}");
+            }
+            None => {
+                println!("{device}: (no value)");
+                all_found = false;
+            }
+        }
+    }
+    Ok(all_found)
+}
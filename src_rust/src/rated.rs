@@ -0,0 +1,47 @@
+// 额定最高工作温度：按 model_family 前缀查内置表（rated_max.toml），--rated-table 可覆盖或补充
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const BUILTIN_TABLE: &str = include_str!("rated_max.toml");
+
+// 键为小写的型号家族前缀
+#[derive(Debug, Default)]
+pub struct RatedTable(BTreeMap<String, f64>);
+
+fn parse(content: &str) -> Result<BTreeMap<String, f64>, toml::de::Error> {
+    let table: BTreeMap<String, f64> = toml::from_str(content)?;
+    Ok(table
+        .into_iter()
+        .map(|(family, max)| (family.to_lowercase(), max))
+        .collect())
+}
+
+impl RatedTable {
+    // 内置表，再合并 path 中的条目；同名前缀以文件为准
+    pub fn load(path: Option<&Path>) -> io::Result<Self> {
+        let mut table = parse(BUILTIN_TABLE).map_err(io::Error::other)?;
+        if let Some(path) = path {
+            let content = fs::read_to_string(path)?;
+            let extra = parse(&content).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid rated table {}: {e}", path.display()),
+                )
+            })?;
+            table.extend(extra);
+        }
+        Ok(RatedTable(table))
+    }
+
+    // 匹配 family 的最长前缀对应的额定最高温度
+    pub fn lookup(&self, family: &str) -> Option<f64> {
+        let family = family.to_lowercase();
+        self.0
+            .iter()
+            .filter(|(prefix, _)| family.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, &max)| max)
+    }
+}
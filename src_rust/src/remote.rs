@@ -0,0 +1,44 @@
+// --remote：通过 SSH 在远程主机上运行本程序的 export 子命令，解析其 JSON 报告后并入本机结果
+use crate::DiskInfo;
+use serde_json::Value;
+use std::io;
+use std::process::{Command, Stdio};
+
+// 远程主机上默认执行的命令，输出与 export --format json 相同的报告
+pub const DEFAULT_REMOTE_COMMAND: &str = "hddtemp_rust export --format json";
+
+// SSH 连接超时（秒），主机不可达时不至于长时间阻塞整次扫描
+const CONNECT_TIMEOUT: u32 = 10;
+
+// 在 host 上执行 command，返回其报告中的设备列表，每个设备都标记上主机名。
+// BatchMode 禁止交互式输入密码；远程命令因温度告警以非零状态退出是正常的，只要输出了报告就算成功
+pub fn query_host(host: &str, command: &str) -> io::Result<Vec<DiskInfo>> {
+    let output = Command::new("ssh")
+        .args(["-o", "BatchMode=yes", "-o"])
+        .arg(format!("ConnectTimeout={CONNECT_TIMEOUT}"))
+        .args(["--", host, command])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| io::Error::new(e.kind(), format!("Failed to run ssh: {e}")))?;
+
+    let report = serde_json::from_slice::<Value>(&output.stdout).map_err(|_| {
+        // ssh 自身出错（连接失败、认证失败）时以 255 退出，错误信息在 stderr 最后一行
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr
+            .lines()
+            .rfind(|l| !l.trim().is_empty())
+            .map(str::trim)
+            .unwrap_or("no JSON report in output");
+        io::Error::other(match output.status.code() {
+            Some(255) => format!("{host} unreachable: {reason}"),
+            _ => format!("{host}: {reason}"),
+        })
+    })?;
+
+    let mut disks: Vec<DiskInfo> = serde_json::from_value(report["disks"].clone())
+        .map_err(|e| io::Error::other(format!("{host}: unexpected report format: {e}")))?;
+    for info in &mut disks {
+        info.host = Some(host.to_string());
+    }
+    Ok(disks)
+}
@@ -0,0 +1,208 @@
+// 把命令行给出的设备（/dev/sdX、LABEL=、UUID= 等）解析为对应的整块磁盘
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+// TAG=value 形式与 /dev/disk/by-* 目录的对应关系
+const TAG_DIRS: [(&str, &str); 4] = [
+    ("LABEL", "by-label"),
+    ("UUID", "by-uuid"),
+    ("PARTLABEL", "by-partlabel"),
+    ("PARTUUID", "by-partuuid"),
+];
+
+// 解析 TAG=value：优先查 udev 维护的 /dev/disk/by-*，没有 udev 时退回 blkid
+fn resolve_tag(tag: &str, value: &str, dir: &str) -> io::Result<PathBuf> {
+    let link = Path::new("/dev/disk").join(dir).join(value);
+    if let Ok(path) = fs::canonicalize(&link) {
+        return Ok(path);
+    }
+
+    let output = Command::new("blkid")
+        .args(["-o", "device", "-t", &format!("{tag}={value}")])
+        .output()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .filter(|_| output.status.success())
+        .map(PathBuf::from)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No device with {tag}={value}"),
+            )
+        })
+}
+
+// 已经提示过映射到整块磁盘的分区
+static NOTED_PARTITIONS: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+// 分区在 sysfs 中位于所属磁盘目录之下，据此找到整块磁盘；本身就是磁盘时原样返回
+fn whole_disk(name: &str) -> String {
+    let class_dir = Path::new("/sys/class/block").join(name);
+    if !class_dir.join("partition").exists() {
+        return name.to_string();
+    }
+    fs::canonicalize(&class_dir)
+        .ok()
+        .and_then(|dir| {
+            dir.parent()
+                .and_then(|p| p.file_name())
+                .map(|n| n.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| name.to_string())
+}
+
+// 把设备路径（可以是分区或符号链接）映射为所在整块磁盘的 /dev 路径，不输出提示
+pub fn disk_of(path: &str) -> String {
+    let resolved = fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path));
+    match resolved.file_name() {
+        Some(name) => format!("/dev/{}", whole_disk(&name.to_string_lossy())),
+        None => path.to_string(),
+    }
+}
+
+// 解析一个设备参数，返回 /dev/<磁盘名>
+pub fn resolve_device(spec: &str) -> io::Result<String> {
+    let path = match spec.split_once('=') {
+        Some((tag, value)) => {
+            let &(tag, dir) = TAG_DIRS
+                .iter()
+                .find(|(t, _)| t.eq_ignore_ascii_case(tag))
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("Unknown device tag in {spec} (expected LABEL, UUID, PARTLABEL or PARTUUID)"),
+                    )
+                })?;
+            resolve_tag(tag, value, dir)?
+        }
+        // 跟随 /dev/disk/by-id 等符号链接
+        None => {
+            fs::canonicalize(spec).map_err(|e| io::Error::new(e.kind(), format!("{spec}: {e}")))?
+        }
+    };
+
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid device {spec}"),
+            )
+        })?;
+    let disk = whole_disk(&name);
+    // 守护进程每次扫描都会重新解析设备，同一个分区只提示一次
+    if disk != name
+        && NOTED_PARTITIONS
+            .lock()
+            .is_ok_and(|mut noted| noted.insert(name.clone()))
+    {
+        eprintln!("Note: {spec} is a partition; querying its disk /dev/{disk}");
+    }
+    Ok(format!("/dev/{disk}"))
+}
+
+// 设备的稳定身份：SCSI/ATA 与 NVMe 的 wwid，没有时用序列号；读不到时返回 None
+pub fn device_identity(device: &str) -> Option<String> {
+    let block = Path::new("/sys/block").join(device.trim_start_matches("/dev/"));
+    [
+        block.join("device/wwid"),
+        block.join("wwid"),
+        block.join("device/serial"),
+    ]
+    .iter()
+    .filter_map(|path| fs::read_to_string(path).ok())
+    .map(|id| id.trim().to_string())
+    .find(|id| !id.is_empty())
+}
+
+// 形如 0000:00:17.0 的 PCI 地址（域:总线:设备.功能）
+fn is_pci_address(name: &str) -> bool {
+    let b = name.as_bytes();
+    b.len() == 12
+        && b[4] == b':'
+        && b[7] == b':'
+        && b[10] == b'.'
+        && name
+            .chars()
+            .filter(|&c| !matches!(c, ':' | '.'))
+            .all(|c| c.is_ascii_hexdigit())
+}
+
+// 设备所在的存储控制器：sysfs 设备路径中最深的一级 PCI 地址，如 HBA 或 NVMe 控制器；
+// 不在 PCI 总线上（或读不到 sysfs）时返回 None
+pub fn controller(device: &str) -> Option<String> {
+    let block = Path::new("/sys/block").join(device.trim_start_matches("/dev/"));
+    let path = fs::canonicalize(block.join("device")).ok()?;
+    path.iter()
+        .rev()
+        .filter_map(|c| c.to_str())
+        .find(|c| is_pci_address(c))
+        .map(str::to_string)
+}
+
+// 设备的连接位置：优先取 udev 的 /dev/disk/by-path 链接名（如 pci-0000:03:00.0-sas-phy2-lun-0），
+// 没有 udev 时退回 sysfs 设备路径（去掉 /sys/devices/ 前缀）
+pub fn bus_path(device: &str) -> Option<String> {
+    let target = fs::canonicalize(device).ok();
+    let by_path = fs::read_dir("/dev/disk/by-path").ok().and_then(|entries| {
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| target.is_some() && fs::canonicalize(entry.path()).ok() == target)
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect();
+        // 同一设备可能有多个链接（如 ata- 与 pci- 两种命名），取最短的一个保证输出稳定
+        names.sort_by_key(|n| (n.len(), n.clone()));
+        names.into_iter().next()
+    });
+    by_path.or_else(|| {
+        let block = Path::new("/sys/block").join(device.trim_start_matches("/dev/"));
+        let path = fs::canonicalize(block.join("device")).ok()?;
+        let relative = path.strip_prefix("/sys/devices").ok()?;
+        Some(relative.to_string_lossy().into_owned())
+    })
+}
+
+// 形如 nvme3 的 NVMe 控制器名
+fn is_nvme_controller(name: &str) -> bool {
+    name.strip_prefix("nvme")
+        .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+// NVMe 命名空间（nvmeXnY）所属的控制器（nvmeZ），不是 NVMe 命名空间时返回 None。
+// 一般情况下 sysfs 的 device 链接直接指向控制器；原生多路径（如 NVMe-oF）下命名空间按子系统编号，
+// 可经多个控制器访问，取 multipath 目录中第一条路径 nvme<子系统>c<控制器>n<命名空间> 的控制器。
+// 两者都查不到时返回 None：设备名中的编号在多路径下是子系统编号，不能当作控制器编号猜测
+pub fn nvme_controller(device: &str) -> Option<String> {
+    let name = device.trim_start_matches("/dev/");
+    let (instance, namespace) = name.strip_prefix("nvme")?.split_once('n')?;
+    if instance.is_empty()
+        || !instance.bytes().all(|b| b.is_ascii_digit())
+        || namespace.is_empty()
+        || !namespace.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let block = Path::new("/sys/block").join(name);
+    let linked = fs::canonicalize(block.join("device"))
+        .ok()
+        .and_then(|p| Some(p.file_name()?.to_string_lossy().into_owned()))
+        .filter(|n| is_nvme_controller(n));
+    let multipath = || {
+        let mut paths: Vec<String> = fs::read_dir(block.join("multipath"))
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect();
+        paths.sort();
+        let (_, controller) = paths.first()?.split_once('c')?;
+        let (controller, _) = controller.split_once('n')?;
+        Some(format!("nvme{controller}")).filter(|n| is_nvme_controller(n))
+    };
+    linked.or_else(multipath)
+}
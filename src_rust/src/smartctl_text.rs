@@ -0,0 +1,176 @@
+// --backend text：不带 --json 运行 smartctl -a，解析文本输出，供无法升级到 smartmontools 7.0 的系统使用。
+// 信息段和属性表被整理成与 smartctl JSON 相同的字段，类型、序列号、WWN、PREFAIL 等判断与 JSON 模式一致
+use crate::cli::QueryArgs;
+use crate::{
+    device_types_for, execute_smartctl, extract_temperature_from_text, finish_reading,
+    parse_smartctl_json, parse_smartctl_version, DiskReading, TempSource,
+};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::io;
+
+// 信息段中 "Key:   value" 形式的行，键转为小写；同名的键以第一次出现的为准
+fn information(text: &str) -> BTreeMap<String, &str> {
+    let mut fields = BTreeMap::new();
+    for line in text.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if !value.is_empty() {
+            fields.entry(key.trim().to_lowercase()).or_insert(value);
+        }
+    }
+    fields
+}
+
+// 去掉千位分隔符后的第一个数，如 "4,000,787,030,016 bytes [4.00 TB]" 中的 4000787030016
+fn leading_number(value: &str) -> Option<u64> {
+    value
+        .split_whitespace()
+        .next()?
+        .replace(',', "")
+        .parse()
+        .ok()
+}
+
+// 属性表的一行，如
+// "194 Temperature_Celsius 0x0022 036 049 000 Old_age Always - 36 (0 17 0 0 0)"
+fn attribute(line: &str) -> Option<Value> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() < 10 || !parts[2].starts_with("0x") {
+        return None;
+    }
+    let id: u64 = parts[0].parse().ok()?;
+    let number = |s: &str| s.parse::<u64>().ok();
+    // WHEN_FAILED 列在 JSON 中为 "now"/"past"，没有失败时为空
+    let when_failed = match parts[8] {
+        "FAILING_NOW" => "now",
+        "In_the_past" => "past",
+        _ => "",
+    };
+    let raw = parts[9..].join(" ");
+    Some(json!({
+        "id": id,
+        "name": parts[1],
+        "value": number(parts[3]),
+        "worst": number(parts[4]),
+        "thresh": number(parts[5]),
+        "when_failed": when_failed,
+        "raw": {"value": number(parts[9]), "string": raw},
+    }))
+}
+
+// 把文本输出整理成 smartctl JSON 的字段，温度另由文本提取
+pub fn parse(text: &str, temp_attr: Option<u32>) -> DiskReading {
+    let info = information(text);
+    let field = |key: &str| info.get(key).copied();
+    let mut raw = Map::new();
+    let protocol = if field("model number").is_some() {
+        Some("NVMe")
+    } else if field("vendor").is_some() && field("product").is_some() {
+        Some("SCSI")
+    } else if field("device model").is_some() {
+        Some("ATA")
+    } else {
+        None
+    };
+    if let Some(protocol) = protocol {
+        raw.insert("device".into(), json!({"protocol": protocol}));
+    }
+    if let Some((major, minor)) = parse_smartctl_version(text) {
+        raw.insert("smartctl".into(), json!({"version": [major, minor]}));
+    }
+    let strings = [
+        ("model_family", field("model family")),
+        (
+            "model_name",
+            field("device model")
+                .or(field("model number"))
+                .or(field("product")),
+        ),
+        ("vendor", field("vendor")),
+        ("serial_number", field("serial number")),
+        ("logical_unit_id", field("logical unit id")),
+    ];
+    for (key, value) in strings {
+        if let Some(value) = value {
+            raw.insert(key.into(), Value::from(value));
+        }
+    }
+    // ATA 的 "LU WWN Device Id: 5 000c50 0ce0a6a14" 即 NAA、OUI 和 ID 拼接而成的 WWN
+    if let Some(wwn) = field("lu wwn device id") {
+        raw.insert(
+            "logical_unit_id".into(),
+            Value::from(format!("0x{}", wwn.replace(' ', ""))),
+        );
+    }
+    if let Some(rate) = field("rotation rate") {
+        let rpm = if rate.starts_with("Solid State") {
+            Some(0)
+        } else {
+            leading_number(rate)
+        };
+        raw.insert("rotation_rate".into(), Value::from(rpm));
+    }
+    if let Some(bytes) = field("user capacity").and_then(leading_number) {
+        raw.insert("user_capacity".into(), json!({"bytes": bytes}));
+    }
+    if let Some(bytes) = field("total nvm capacity").and_then(leading_number) {
+        raw.insert("nvme_total_capacity".into(), Value::from(bytes));
+    }
+    let mut health = Map::new();
+    if let Some(bits) = field("critical warning")
+        .and_then(|v| u64::from_str_radix(v.trim_start_matches("0x"), 16).ok())
+    {
+        health.insert("critical_warning".into(), Value::from(bits));
+    }
+    // "Temperature Sensor 2:  47 Celsius"，缺少的编号在 JSON 中为 null
+    let sensors: Vec<Option<u64>> = (1..=8)
+        .map(|n| field(&format!("temperature sensor {n}")).and_then(leading_number))
+        .collect();
+    if let Some(last) = sensors.iter().rposition(Option::is_some) {
+        health.insert("temperature_sensors".into(), json!(sensors[..=last]));
+    }
+    if !health.is_empty() {
+        raw.insert(
+            "nvme_smart_health_information_log".into(),
+            Value::Object(health),
+        );
+    }
+    if let Some(trip) = field("drive trip temperature").and_then(leading_number) {
+        raw.insert("temperature".into(), json!({"drive_trip": trip}));
+    }
+    let attributes: Vec<Value> = text.lines().filter_map(attribute).collect();
+    if !attributes.is_empty() {
+        raw.insert("ata_smart_attributes".into(), json!({"table": attributes}));
+    }
+
+    let mut reading = parse_smartctl_json(Value::Object(raw));
+    reading.temperature = extract_temperature_from_text(text, temp_attr).map(|t| t as f64);
+    reading.temperature_source = reading.temperature.map(|_| TempSource::Text);
+    reading
+}
+
+// 与 JSON 模式一样依次尝试各个 -d 类型，返回第一个有温度的读数；都没有温度时返回第一个认出型号的读数
+pub fn get_disk_info_and_temperature(device: &str, query: &QueryArgs) -> io::Result<DiskReading> {
+    let mut partial = None;
+    for device_type in device_types_for(device, &query.smartctl) {
+        let mut args = vec!["-a"];
+        if !device_type.is_empty() {
+            args.extend(["-d", device_type]);
+        }
+        args.push(device);
+        let output = execute_smartctl(&query.smartctl, &args)?;
+        let reading = parse(&String::from_utf8_lossy(&output.stdout), query.temp_attr);
+        if reading.temperature.is_some() {
+            return Ok(finish_reading(reading, device, query));
+        }
+        if reading.model.is_some() {
+            partial.get_or_insert(reading);
+        }
+    }
+    partial
+        .map(|reading| finish_reading(reading, device, query))
+        .ok_or_else(|| io::Error::other(format!("Failed for device: {}", device)))
+}
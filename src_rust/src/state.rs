@@ -0,0 +1,279 @@
+// 跨进程持久化的运行状态（JSON 文件），用于空闲检测等需要历史信息的功能
+use crate::DiskInfo;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// 默认状态文件路径
+pub const DEFAULT_STATE_FILE: &str = "/var/lib/hddtemp_rust/state.json";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct State {
+    // 设备名 -> 最近一次观察到的 I/O 活动
+    #[serde(default)]
+    pub activity: BTreeMap<String, Activity>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Activity {
+    // /sys/block/<dev>/stat 中完成的 I/O 次数之和
+    pub io_count: u64,
+    // io_count 最近一次变化的时间（Unix 秒）
+    pub last_change: u64,
+}
+
+// 写入 JSON 文件：先写临时文件再重命名，避免并发读到半个文件
+fn write_json_atomic<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, serde_json::to_vec(value)?)?;
+    fs::rename(&tmp, path)
+}
+
+// FNV-1a（64 位）：算法固定，不随 Rust 版本变化，摘要可以写进文件供之后的进程比较
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl State {
+    // 读取状态文件，不存在或损坏时从空状态开始
+    pub fn load(path: &Path) -> State {
+        match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                eprintln!("Ignoring corrupt state file {}: {e}", path.display());
+                State::default()
+            }),
+            Err(_) => State::default(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        write_json_atomic(path, self)
+    }
+
+    // 更新设备的 I/O 计数，返回设备已空闲的秒数；首次见到或无法读取时返回 None
+    pub fn observe_activity(&mut self, device: &str, now: u64) -> Option<u64> {
+        let io_count = read_io_count(device)?;
+        match self.activity.get_mut(device) {
+            Some(entry) if entry.io_count == io_count => {
+                Some(now.saturating_sub(entry.last_change))
+            }
+            Some(entry) => {
+                entry.io_count = io_count;
+                entry.last_change = now;
+                Some(0)
+            }
+            None => {
+                self.activity.insert(
+                    device.to_string(),
+                    Activity {
+                        io_count,
+                        last_change: now,
+                    },
+                );
+                None
+            }
+        }
+    }
+}
+
+// 读取块设备完成的读、写、discard、flush 次数之和（smartctl 的直通命令不计入其中）
+fn read_io_count(device: &str) -> Option<u64> {
+    let name = device.trim_start_matches("/dev/");
+    let stat = fs::read_to_string(Path::new("/sys/block").join(name).join("stat")).ok()?;
+    let fields: Vec<u64> = stat
+        .split_whitespace()
+        .filter_map(|f| f.parse().ok())
+        .collect();
+    if fields.len() < 5 {
+        return None;
+    }
+    // 字段 0/4/11/15 分别为读、写、discard、flush 完成次数，旧内核没有后两者
+    Some([0, 4, 11, 15].iter().filter_map(|&i| fields.get(i)).sum())
+}
+
+// --scan-cache：上次扫描中每个设备成功使用的 smartctl 设备类型（smartctl JSON 的 device.type），
+// 下次直接先试该类型，省去逐个尝试 -d 类型的开销
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ScanCache {
+    // 生成时间（Unix 秒）
+    pub created: u64,
+    // 设备名 -> 设备类型，包含生成时的全部设备（没有读数的设备类型为 None）
+    pub devices: BTreeMap<String, Option<String>>,
+}
+
+impl ScanCache {
+    // 读取缓存，不存在、损坏、超过 ttl 秒或设备集合有变化时返回 None
+    pub fn load_valid(path: &Path, ttl: u64, devices: &[String], now: u64) -> Option<ScanCache> {
+        let cache: ScanCache = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+        let same_devices = cache.devices.len() == devices.len()
+            && devices.iter().all(|d| cache.devices.contains_key(d));
+        (same_devices && now.saturating_sub(cache.created) < ttl).then_some(cache)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        write_json_atomic(path, self)
+    }
+}
+
+// --min-interval：每个设备最近一次实际查询到的结果，保存在状态文件旁的 readings.json，
+// 使短时间内反复运行的多个进程共用同一次查询，不会每次都去访问硬盘
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ReadingCache {
+    // 设备名 -> 最近一次查询
+    #[serde(default)]
+    pub readings: BTreeMap<String, CachedReading>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CachedReading {
+    // 查询时间（Unix 秒）
+    pub taken: u64,
+    // 查询参数的摘要，参数不同的运行不沿用这条读数
+    #[serde(default)]
+    pub key: u64,
+    pub info: DiskInfo,
+    // DiskInfo 序列化时省略、但 --scan-cache 和报告顶层需要的字段
+    #[serde(default)]
+    pub smartctl_type: Option<String>,
+    #[serde(default)]
+    pub smartctl_version: Option<String>,
+}
+
+impl ReadingCache {
+    pub fn path_for(state_file: &Path) -> PathBuf {
+        state_file.with_file_name("readings.json")
+    }
+
+    // 不存在或损坏时从空记录开始
+    pub fn load(path: &Path) -> ReadingCache {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        write_json_atomic(path, self)
+    }
+
+    // 以相同参数（key）查询、且查询时间距今不足 min_interval 秒的读数，
+    // cached_seconds 为其已过去的秒数
+    pub fn fresh(&self, key: u64, min_interval: u64, now: u64) -> BTreeMap<String, DiskInfo> {
+        self.readings
+            .iter()
+            .filter(|(_, cached)| {
+                cached.key == key && now.saturating_sub(cached.taken) < min_interval
+            })
+            .map(|(device, cached)| {
+                let info = DiskInfo {
+                    cached_seconds: Some(now.saturating_sub(cached.taken)),
+                    smartctl_type: cached.smartctl_type.clone(),
+                    smartctl_version: cached.smartctl_version.clone(),
+                    ..cached.info.clone()
+                };
+                (device.clone(), info)
+            })
+            .collect()
+    }
+
+    // 记下本次实际查询的结果，并丢弃已过期的旧记录，文件不会随设备变动而无限增长
+    pub fn record(&mut self, results: &[DiskInfo], key: u64, min_interval: u64, now: u64) {
+        self.readings
+            .retain(|_, cached| now.saturating_sub(cached.taken) < min_interval);
+        for info in results {
+            self.readings.insert(
+                info.device.clone(),
+                CachedReading {
+                    taken: now,
+                    key,
+                    info: info.clone(),
+                    smartctl_type: info.smartctl_type.clone(),
+                    smartctl_version: info.smartctl_version.clone(),
+                },
+            );
+        }
+    }
+}
+
+// --alert-cooldown：每个设备最近一次触发 --on-crit 的时间，保存在状态文件旁的 alerts.json，
+// 使 cron 定时运行的多个进程之间也能限制告警频率
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AlertLog {
+    // 设备名 -> 最近一次告警时间（Unix 秒）
+    #[serde(default)]
+    pub last_alert: BTreeMap<String, u64>,
+}
+
+impl AlertLog {
+    pub fn path_for(state_file: &Path) -> PathBuf {
+        state_file.with_file_name("alerts.json")
+    }
+
+    // 不存在或损坏时从空记录开始
+    pub fn load(path: &Path) -> AlertLog {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        write_json_atomic(path, self)
+    }
+
+    // 距上次告警不足 cooldown 秒的设备仍在冷却中
+    pub fn cooling_down(&self, device: &str, cooldown: u64, now: u64) -> bool {
+        self.last_alert
+            .get(device)
+            .is_some_and(|&last| now.saturating_sub(last) < cooldown)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fnv1a_matches_reference_values() {
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x85944171f73967e8);
+    }
+
+    #[test]
+    fn reading_cache_reuses_only_recent_readings_with_the_same_key() {
+        let info = DiskInfo {
+            device: "/dev/sda".to_string(),
+            temperature: Some(36.0),
+            smartctl_type: Some("sat".to_string()),
+            ..Default::default()
+        };
+        let mut cache = ReadingCache::default();
+        cache.record(&[info], 7, 10, 1000);
+        let fresh = cache.fresh(7, 10, 1004);
+        assert_eq!(fresh["/dev/sda"].cached_seconds, Some(4));
+        assert_eq!(fresh["/dev/sda"].temperature, Some(36.0));
+        assert_eq!(fresh["/dev/sda"].smartctl_type.as_deref(), Some("sat"));
+        // 参数不同（如换了 --warn）或超过间隔时都重新查询
+        assert!(cache.fresh(8, 10, 1004).is_empty());
+        assert!(cache.fresh(7, 10, 1010).is_empty());
+        // 过期的记录在下次写入时丢弃
+        cache.record(&[], 7, 10, 1010);
+        assert!(cache.readings.is_empty());
+    }
+}
@@ -0,0 +1,252 @@
+// smartctl 解析的回归测试，输入为 tests/fixtures/smartctl 下抓取的真实输出。
+// 遇到"某块盘显示 N/A"一类问题时，把该盘的输出存为新的夹具并在这里加上断言
+
+use super::*;
+
+fn fixture(name: &str) -> Vec<u8> {
+    let path = format!(
+        "{}/tests/fixtures/smartctl/{name}",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    std::fs::read(&path).unwrap_or_else(|e| panic!("failed to read fixture {path}: {e}"))
+}
+
+fn parse(name: &str) -> DiskReading {
+    parse_smartctl_output(&fixture(name), None)
+        .unwrap_or_else(|e| panic!("failed to parse fixture {name}: {e}"))
+}
+
+#[test]
+fn sata_json() {
+    let reading = parse("sata.json");
+    assert_eq!(reading.vendor.as_deref(), Some("Seagate"));
+    assert_eq!(reading.model.as_deref(), Some("ST4000VN008-2DR166"));
+    assert_eq!(reading.serial().as_deref(), Some("ZDH1ABCD"));
+    assert_eq!(reading.temperature, Some(36.0));
+    assert_eq!(reading.temperature_source, Some(TempSource::Json));
+    assert_eq!(reading.drive_type(), Some(DriveType::Hdd));
+    assert_eq!(reading.wwn().as_deref(), Some("0x5000c500ce0a6a14"));
+    assert_eq!(reading.smartctl_version().as_deref(), Some("7.3"));
+}
+
+#[test]
+fn sata_attribute_temperatures_ignore_min_max_bytes() {
+    let reading = parse("sata.json");
+    assert_eq!(
+        reading.ata_attribute_temperatures(),
+        BTreeMap::from([(190, 34), (194, 36)])
+    );
+}
+
+#[test]
+fn sas_json() {
+    let reading = parse("sas.json");
+    assert_eq!(reading.vendor.as_deref(), Some("SEAGATE"));
+    assert_eq!(reading.model.as_deref(), Some("SEAGATE ST4000NM0023"));
+    assert_eq!(reading.temperature, Some(31.0));
+    assert_eq!(reading.drive_trip(), Some(68));
+    assert_eq!(reading.wwn().as_deref(), Some("0x5000c50057a1b2c3"));
+    assert_eq!(reading.drive_type(), Some(DriveType::Hdd));
+}
+
+#[test]
+fn nvme_json() {
+    let reading = parse("nvme.json");
+    assert_eq!(
+        reading.model.as_deref(),
+        Some("Samsung SSD 970 EVO Plus 1TB")
+    );
+    assert_eq!(reading.temperature, Some(42.0));
+    assert_eq!(reading.drive_type(), Some(DriveType::Nvme));
+    assert_eq!(reading.capacity(), Some(1000204886016));
+    assert!(reading.nvme_critical_warnings().is_empty());
+    assert_eq!(
+        reading.nvme_sensor_temperatures(),
+        BTreeMap::from([(1, 42), (2, 47)])
+    );
+}
+
+#[test]
+fn usb_bridge_temperature_from_attributes() {
+    // 经 USB 桥接（-d sat）时没有顶层 temperature 字段，只能从属性表中取
+    let reading = parse("usb.json");
+    assert_eq!(reading.vendor, None);
+    assert_eq!(reading.model.as_deref(), Some("WDC WD40EFRX-68N32N0"));
+    assert_eq!(reading.temperature, Some(35.0));
+    assert_eq!(reading.temperature_source, Some(TempSource::Json));
+}
+
+#[test]
+fn text_fallback() {
+    let reading = parse("text.txt");
+    assert_eq!(reading.temperature, Some(37.0));
+    assert_eq!(reading.temperature_source, Some(TempSource::Text));
+    assert!(reading.raw.is_none());
+}
+
+#[test]
+fn text_prefers_requested_attribute() {
+    let text = String::from_utf8(fixture("text.txt")).unwrap();
+    assert_eq!(extract_temperature_from_text(&text, None), Some(37));
+    assert_eq!(extract_temperature_from_text(&text, Some(190)), Some(35));
+}
+
+#[test]
+fn unparseable_output_is_an_error() {
+    let err = parse_smartctl_output(b"smartctl: command not found", None).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn rated_max_uses_longest_family_prefix() {
+    let table = rated::RatedTable::load(None).unwrap();
+    let reading = parse("sata.json");
+    assert_eq!(reading.model_family(), Some("Seagate IronWolf"));
+    assert_eq!(table.lookup("Seagate IronWolf"), Some(70.0));
+    assert_eq!(table.lookup("Seagate Exos X16"), Some(60.0));
+    assert_eq!(
+        table.lookup("toshiba mg07aca enterprise capacity hdd"),
+        Some(55.0)
+    );
+    assert_eq!(table.lookup("Samsung SSD 970"), None);
+}
+
+#[test]
+fn smartctl_version_from_banner() {
+    let text = String::from_utf8(fixture("text.txt")).unwrap();
+    assert_eq!(parse_smartctl_version(&text), Some((6, 2)));
+    assert_eq!(
+        parse_smartctl_version("smartctl 7.4 2023-08-01 r5530 [x86_64-linux-6.8.0] (local build)"),
+        Some((7, 4))
+    );
+    assert_eq!(parse_smartctl_version("sudo: a password is required"), None);
+}
+
+#[test]
+fn text_backend_sata() {
+    let text = String::from_utf8(fixture("sata.txt")).unwrap();
+    let reading = smartctl_text::parse(&text, None);
+    assert_eq!(reading.vendor.as_deref(), Some("Seagate"));
+    assert_eq!(reading.model.as_deref(), Some("ST4000VN008-2DR166"));
+    assert_eq!(reading.serial().as_deref(), Some("ZDH1ABCD"));
+    assert_eq!(reading.wwn().as_deref(), Some("0x5000c500ce0a6a14"));
+    assert_eq!(reading.drive_type(), Some(DriveType::Hdd));
+    assert_eq!(reading.capacity(), Some(4000787030016));
+    assert_eq!(reading.smartctl_version().as_deref(), Some("6.2"));
+    assert_eq!(reading.temperature, Some(36.0));
+    assert_eq!(reading.temperature_source, Some(TempSource::Text));
+    assert_eq!(
+        reading.ata_attribute_temperatures(),
+        BTreeMap::from([(190, 34), (194, 36)])
+    );
+    assert!(reading.failing_attributes().is_empty());
+    let reading = smartctl_text::parse(&text, Some(190));
+    assert_eq!(reading.temperature, Some(34.0));
+}
+
+#[test]
+fn text_backend_sas() {
+    let text = String::from_utf8(fixture("sas.txt")).unwrap();
+    let reading = smartctl_text::parse(&text, None);
+    assert_eq!(reading.vendor.as_deref(), Some("SEAGATE"));
+    assert_eq!(reading.model.as_deref(), Some("ST4000NM0023"));
+    assert_eq!(reading.serial().as_deref(), Some("Z1Z2ABCD0000C4321ABC"));
+    assert_eq!(reading.wwn().as_deref(), Some("0x5000c50057a1b2c3"));
+    assert_eq!(reading.temperature, Some(31.0));
+    assert_eq!(reading.drive_trip(), Some(68));
+}
+
+#[test]
+fn text_backend_nvme() {
+    let text = String::from_utf8(fixture("nvme.txt")).unwrap();
+    let reading = smartctl_text::parse(&text, None);
+    assert_eq!(
+        reading.model.as_deref(),
+        Some("Samsung SSD 970 EVO Plus 1TB")
+    );
+    assert_eq!(reading.drive_type(), Some(DriveType::Nvme));
+    assert_eq!(reading.capacity(), Some(1000204886016));
+    assert_eq!(reading.temperature, Some(42.0));
+    assert!(reading.nvme_critical_warnings().is_empty());
+    assert_eq!(
+        reading.nvme_sensor_temperatures(),
+        BTreeMap::from([(1, 42), (2, 47)])
+    );
+}
+
+#[test]
+fn unit_correction_boundaries() {
+    // 120°C 及以下照常采用
+    assert_eq!(correct_temperature_unit(45.0), None);
+    assert_eq!(correct_temperature_unit(120.0), None);
+    // 只有华氏度换算合理：121°F = 49.4°C，149°F = 65°C（14.9°C 低于 15°C）
+    assert_eq!(
+        correct_temperature_unit(121.0),
+        Some((49.4, UnitCorrection::Fahrenheit))
+    );
+    assert_eq!(
+        correct_temperature_unit(149.0),
+        Some((65.0, UnitCorrection::Fahrenheit))
+    );
+    // 两种换算都合理时无法判断：150 既可能是 15.0°C 也可能是 65.6°C；176°F 恰为 80°C
+    assert_eq!(correct_temperature_unit(150.0), None);
+    assert_eq!(correct_temperature_unit(176.0), None);
+    // 只有 0.1°C 换算合理：177°F 超出 80°C；800 恰为 80.0°C，801 两种都不合理
+    assert_eq!(
+        correct_temperature_unit(177.0),
+        Some((17.7, UnitCorrection::Tenths))
+    );
+    assert_eq!(
+        correct_temperature_unit(800.0),
+        Some((80.0, UnitCorrection::Tenths))
+    );
+    assert_eq!(correct_temperature_unit(801.0), None);
+}
+
+#[test]
+fn assumed_kelvin_units() {
+    // 开尔文不参与自动判断：310 会被当作 0.1°C，只能用 --assume-unit 指定
+    assert_eq!(
+        correct_temperature_unit(310.0),
+        Some((31.0, UnitCorrection::Tenths))
+    );
+    let reading = |t: f64| DiskReading {
+        temperature: Some(t),
+        ..parse("sata.json")
+    };
+    let kelvin = reading(310.15).with_assumed_unit(cli::TempUnit::Kelvin);
+    assert_eq!(kelvin.temperature, Some(37.0));
+    assert_eq!(kelvin.unit_correction, Some(UnitCorrection::Kelvin));
+    let decikelvin = reading(3101.5).with_assumed_unit(cli::TempUnit::Decikelvin);
+    assert_eq!(decikelvin.temperature, Some(37.0));
+    let celsius = reading(37.0).with_assumed_unit(cli::TempUnit::Celsius);
+    assert_eq!(celsius.temperature, Some(37.0));
+    assert_eq!(celsius.unit_correction, None);
+}
+
+#[test]
+fn nvme_controller_is_not_guessed_from_the_name() {
+    // sysfs 中没有这个命名空间时不按名称猜测控制器，避免把多路径子系统编号当作控制器
+    assert_eq!(resolve::nvme_controller("/dev/nvme97n2"), None);
+    assert_eq!(resolve::nvme_controller("/dev/nvme0"), None);
+    assert_eq!(resolve::nvme_controller("/dev/sda"), None);
+}
+
+#[test]
+fn exec_wrapper_honours_shell_quoting() {
+    let smartctl = SmartctlArgs {
+        smartctl_path: "smartctl".into(),
+        exec_wrapper: Some("printf '%s|' 'two words'".to_string()),
+        types: Vec::new(),
+        power_mode: None,
+        timeout: 1,
+        retry_timeout: 1,
+    };
+    let output = smartctl_command(&smartctl, &["-x", "/dev/disk by-id"])
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "two words|-x|/dev/disk by-id|"
+    );
+}
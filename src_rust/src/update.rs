@@ -0,0 +1,43 @@
+// --check-update：查询 GitHub releases 中的最新版本并与当前版本比较，只提示不下载
+use serde_json::Value;
+use std::io;
+
+const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/LiveQiu/hddtemp_rust/releases/latest";
+
+// "v0.1.3" -> [0, 1, 3]，无法解析的部分视为 0
+fn version_parts(version: &str) -> Vec<u64> {
+    version
+        .trim_start_matches('v')
+        .split(['.', '-', '+'])
+        .take(3)
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+pub fn check() -> io::Result<()> {
+    let current = env!("CARGO_PKG_VERSION");
+    let body = ureq::get(LATEST_RELEASE_URL)
+        .set(
+            "User-Agent",
+            concat!("hddtemp_rust/", env!("CARGO_PKG_VERSION")),
+        )
+        .set("Accept", "application/vnd.github+json")
+        .call()
+        .map_err(io::Error::other)?
+        .into_string()?;
+    let release: Value = serde_json::from_str(&body)?;
+    let latest = release["tag_name"]
+        .as_str()
+        .ok_or_else(|| io::Error::other("Release has no tag_name"))?;
+
+    if version_parts(latest) > version_parts(current) {
+        println!("A newer version is available: {latest} (installed: {current})");
+        if let Some(url) = release["html_url"].as_str() {
+            println!("{url}");
+        }
+    } else {
+        println!("hddtemp_rust {current} is up to date");
+    }
+    Ok(())
+}